@@ -1,4 +1,12 @@
-use std::{borrow::Cow, fmt::Display, fs::File, io::stdin, ops::Mul, path::PathBuf, process::exit};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    fs::read_to_string,
+    io::{self, stdin},
+    ops::Mul,
+    path::PathBuf,
+    process::exit,
+};
 
 use chrono::DateTime;
 use chrono_tz::Tz;
@@ -14,13 +22,18 @@ use tabled::{
 };
 
 use ocpi_tariffs::{
-    ocpi::{cdr::Cdr, tariff::OcpiTariff},
-    pricer::{DimensionReport, Pricer, Report},
+    interpretation::{interpret, Interpretation},
+    ocpi::{self, cdr::Cdr, tariff::OcpiTariff, Version},
+    options::{PricerOptions, Profile},
+    pricer::{DimensionReport, PriceLimit, Pricer, Report},
     types::{
         electricity::Kwh,
         money::{Money, Price, Vat},
+        period::DimensionType,
+        rounding::RoundingScope,
         time::HoursDecimal,
     },
+    validation::validate_cdr,
 };
 
 use crate::{error::Error, Result};
@@ -46,13 +59,27 @@ pub enum Command {
     /// a tariff that is contained in the CDR itself.
     ///
     /// This command will show the differences between the calculated totals and the totals
-    /// contained in the provided CDR.
+    /// contained in the provided CDR, and any structural issues of the CDR itself.
     Validate(Validate),
     /// Analyze a given charge detail record (CDR) against either a provided tariff structure or a
     /// tariff that is contained in the CDR itself.
     ///
     /// This command will show you a breakdown of all the calculated costs.
     Analyze(Analyze),
+    /// Price a given charge detail record (CDR) using every reading of the ambiguous parts of the
+    /// OCPI specification, against either a provided tariff structure or a tariff that is
+    /// contained in the CDR itself.
+    ///
+    /// This command will show the totals of each reading next to the totals contained in the
+    /// provided CDR, and highlight the readings that match. The `--profile` option is ignored.
+    Interpret(Interpret),
+    /// Explain the pricing of a given charge detail record (CDR) against either a provided tariff
+    /// structure or a tariff that is contained in the CDR itself.
+    ///
+    /// This command will show for each period which tariff elements were considered, the outcome
+    /// of each of their restrictions and which element supplied the price component of each
+    /// dimension.
+    Explain(Explain),
 }
 
 impl Command {
@@ -60,6 +87,8 @@ impl Command {
         match self {
             Self::Validate(args) => args.run(),
             Self::Analyze(args) => args.run(),
+            Self::Interpret(args) => args.run(),
+            Self::Explain(args) => args.run(),
         }
     }
 }
@@ -75,12 +104,20 @@ pub struct TariffArgs {
     ///
     /// If no path is provided, then the tariff is inferred to be contained inside the
     /// provided CDR. If the CDR contains multiple tariff structures, the first valid tariff
-    /// will be used.
+    /// will be used until another tariff becomes valid during the session.
     #[arg(short = 't', long)]
     tariff: Option<PathBuf>,
     /// Timezone for evaluating any local times contained in the tariff structure.
     #[arg(short = 'z', long, default_value = "Europe/Amsterdam")]
     timezone: Tz,
+    /// The OCPI version of the charge detail record and the tariff structure, either `2.1.1` or
+    /// `2.2.1`.
+    #[arg(short = 'o', long, default_value = "2.2.1")]
+    ocpi_version: Version,
+    /// The reading of the ambiguous parts of the OCPI specification, either `strict`, `ocpi-3`
+    /// or `period-start`.
+    #[arg(short = 'p', long, default_value = "strict")]
+    profile: Profile,
 }
 
 impl TariffArgs {
@@ -98,32 +135,49 @@ impl TariffArgs {
             .unwrap_or_else(|| "<CDR-tariff>".into())
     }
 
-    fn load_all(&self) -> Result<(Report, Cdr, Option<OcpiTariff>)> {
+    fn load(&self) -> Result<(Cdr, Option<OcpiTariff>)> {
         let cdr: Cdr = if let Some(cdr_path) = &self.cdr {
-            let file = File::open(cdr_path).map_err(|e| Error::file(cdr_path.clone(), e))?;
-            serde_json::from_reader(&file)
+            let json = read_to_string(cdr_path).map_err(|e| Error::file(cdr_path.clone(), e))?;
+            ocpi::cdr_from_str(&json, self.ocpi_version)
                 .map_err(|e| Error::deserialize(cdr_path.display(), "CDR", e))?
         } else {
-            let mut stdin = stdin().lock();
-            serde_json::from_reader(&mut stdin)
+            let json = io::read_to_string(stdin().lock())
+                .map_err(|e| Error::file(PathBuf::from("<stdin>"), e))?;
+            ocpi::cdr_from_str(&json, self.ocpi_version)
                 .map_err(|e| Error::deserialize("<stdin>", "CDR", e))?
         };
 
         let tariff: Option<OcpiTariff> = if let Some(path) = &self.tariff {
-            let file = File::open(path).map_err(|e| Error::file(path.clone(), e))?;
-            serde_json::from_reader(&file)
-                .map_err(|e| Error::deserialize(path.display(), "tariff", e))?
+            let json = read_to_string(path).map_err(|e| Error::file(path.clone(), e))?;
+            let tariff = ocpi::tariff_from_str(&json, self.ocpi_version)
+                .map_err(|e| Error::deserialize(path.display(), "tariff", e))?;
+            Some(tariff)
         } else {
             None
         };
 
+        Ok((cdr, tariff))
+    }
+
+    fn load_all(&self) -> Result<(Report, Cdr, Option<OcpiTariff>)> {
+        self.load_explained(false)
+    }
+
+    /// Load and price the CDR, recording the trace of the tariff elements when `explain` is set.
+    fn load_explained(&self, explain: bool) -> Result<(Report, Cdr, Option<OcpiTariff>)> {
+        let (cdr, tariff) = self.load()?;
+
         let pricer = if let Some(tariff) = tariff.clone() {
             Pricer::with_tariffs(&cdr, &[tariff], self.timezone)
         } else {
             Pricer::new(&cdr, self.timezone)
         };
 
-        let report = pricer.build_report().map_err(Error::Internal)?;
+        let report = pricer
+            .options(PricerOptions::from_profile(self.profile))
+            .explain(explain)
+            .build_report()
+            .map_err(Error::Internal)?;
 
         Ok((report, cdr, tariff))
     }
@@ -202,6 +256,21 @@ impl Validate {
             style(self.args.timezone).blue(),
         );
 
+        let issues = validate_cdr(&cdr);
+
+        if !issues.is_empty() {
+            println!(
+                "The CDR contains {} structural issue(s):",
+                style(issues.len()).yellow().bold()
+            );
+
+            for issue in &issues {
+                println!("  - {issue}");
+            }
+        }
+
+        print_warnings(&report);
+
         let mut table = ValidateTable { rows: Vec::new() };
 
         table.row(report.total_time, Some(cdr.total_time), "Total Time");
@@ -242,6 +311,11 @@ impl Validate {
             cdr.total_parking_cost,
             "Total Parking cost",
         );
+        table.price_row(
+            report.total_reservation_cost.with_scale(),
+            cdr.total_reservation_cost,
+            "Total Reservation cost",
+        );
 
         let valid = table.valid_rows();
         let all_valid = valid.iter().all(|&s| s);
@@ -282,6 +356,267 @@ impl Validate {
     }
 }
 
+#[derive(Parser)]
+pub struct Interpret {
+    #[command(flatten)]
+    args: TariffArgs,
+}
+
+#[derive(Tabled)]
+struct InterpretRow {
+    #[tabled(rename = "Flat fee")]
+    flat_fee: String,
+    #[tabled(rename = "Step size")]
+    step_size: String,
+    #[tabled(rename = "Time step size")]
+    time_step_size: String,
+    #[tabled(rename = "Restrictions")]
+    restrictions: String,
+    #[tabled(rename = "Bounds")]
+    bounds: String,
+    #[tabled(rename = "Rounding")]
+    rounding: String,
+    #[tabled(rename = "Cost excl. VAT")]
+    cost_excl_vat: String,
+    #[tabled(rename = "Cost incl. VAT")]
+    cost_incl_vat: String,
+    #[tabled(rename = "Energy")]
+    energy: String,
+    #[tabled(rename = "Time")]
+    time: String,
+    #[tabled(rename = "Matches")]
+    matches: bool,
+}
+
+impl Interpret {
+    fn run(self) -> Result<()> {
+        let (cdr, tariff) = self.args.load()?;
+
+        println!(
+            "\n{} `{}` with tariff `{}`, using timezone `{}`:",
+            style("Interpreting").green().bold(),
+            style(self.args.cdr_name()).blue(),
+            style(self.args.tariff_name()).blue(),
+            style(self.args.timezone).blue(),
+        );
+
+        let tariffs = tariff.map(|tariff| vec![tariff]);
+        let interpretations = interpret(&cdr, tariffs.as_deref(), self.args.timezone);
+
+        let mut rows = vec![InterpretRow {
+            flat_fee: "CDR".into(),
+            step_size: String::new(),
+            time_step_size: String::new(),
+            restrictions: String::new(),
+            bounds: String::new(),
+            rounding: String::new(),
+            cost_excl_vat: cdr.total_cost.excl_vat.to_string(),
+            cost_incl_vat: cdr.total_cost.incl_vat.to_string(),
+            energy: cdr.total_energy.to_string(),
+            time: cdr.total_time.to_string(),
+            matches: true,
+        }];
+
+        for interpretation in &interpretations {
+            let Interpretation {
+                options, rounding, ..
+            } = interpretation;
+
+            let (cost_excl_vat, cost_incl_vat, energy, time) = match &interpretation.report {
+                Ok(report) => (
+                    report.total_cost.excl_vat.with_scale().to_string(),
+                    report.total_cost.incl_vat.with_scale().to_string(),
+                    report.total_energy.with_scale().to_string(),
+                    report.total_time.to_string(),
+                ),
+                Err(err) => (err.to_string(), String::new(), String::new(), String::new()),
+            };
+
+            let rounding = if rounding.scope == RoundingScope::Summary {
+                "None".to_string()
+            } else {
+                format!("{:?}", rounding.mode)
+            };
+
+            rows.push(InterpretRow {
+                flat_fee: format!("{:?}", options.flat_fee),
+                step_size: format!("{:?}", options.step_size_scope),
+                time_step_size: format!("{:?}", options.time_step_size),
+                restrictions: format!("{:?}", options.restriction_check),
+                bounds: format!("{:?}", options.restriction_bound),
+                rounding,
+                cost_excl_vat,
+                cost_incl_vat,
+                energy,
+                time,
+                matches: interpretation.matches(&cdr),
+            });
+        }
+
+        let matches: Vec<bool> = rows.iter().map(|row| row.matches).collect();
+        let match_count = interpretations
+            .iter()
+            .filter(|interpretation| interpretation.matches(&cdr))
+            .count();
+
+        // The header and the row of the CDR are bold, matching readings are highlighted.
+        let format_matches = Modify::new(Rows::new(..)).with(Format::positioned(|row, (i, _)| {
+            let row = style(row);
+            if i <= 1 {
+                row.bold().to_string()
+            } else if matches[i - 1] {
+                row.green().to_string()
+            } else {
+                row.dim().to_string()
+            }
+        }));
+
+        println!(
+            "{}",
+            Table::new(rows).with(Style::modern()).with(format_matches)
+        );
+
+        if match_count == 0 {
+            println!(
+                "{} reading matches the totals in the CDR.\n",
+                style("No").red().bold()
+            );
+
+            exit(1);
+        }
+
+        println!(
+            "{} of {} readings match the totals in the CDR.\n",
+            style(match_count).green().bold(),
+            interpretations.len()
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+pub struct Explain {
+    #[command(flatten)]
+    args: TariffArgs,
+}
+
+#[derive(Tabled)]
+struct ElementRow {
+    #[tabled(rename = "Element")]
+    element: usize,
+    #[tabled(rename = "Active")]
+    active: bool,
+    #[tabled(rename = "Restriction")]
+    restriction: String,
+    #[tabled(rename = "Bound")]
+    bound: String,
+    #[tabled(rename = "Value")]
+    value: String,
+    #[tabled(rename = "Valid")]
+    valid: String,
+}
+
+#[derive(Tabled)]
+struct DimensionRow {
+    #[tabled(rename = "Dimension")]
+    dimension: DimensionType,
+    #[tabled(rename = "Considered")]
+    considered: String,
+    #[tabled(rename = "Element")]
+    element: OptionDisplay<usize>,
+}
+
+impl Explain {
+    fn run(self) -> Result<()> {
+        let (report, _, _) = self.args.load_explained(true)?;
+
+        println!(
+            "\n{} `{}` with tariff `{}`, using timezone `{}`:",
+            style("Explaining").green().bold(),
+            style(self.args.cdr_name()).blue(),
+            style(self.args.tariff_name()).blue(),
+            style(self.args.timezone).blue(),
+        );
+
+        print_warnings(&report);
+
+        for (index, period) in report.trace.unwrap_or_default().into_iter().enumerate() {
+            println!(
+                "\nPeriod {} starting at {}, priced by tariff `{}`:",
+                style(index + 1).bold(),
+                style(format_time(
+                    &period.start_date_time.with_timezone(&self.args.timezone)
+                ))
+                .blue(),
+                style(&period.tariff_id).blue(),
+            );
+
+            let mut rows = Vec::new();
+
+            for element in &period.elements {
+                let restrictions = element.restrictions.iter().map(|restriction| ElementRow {
+                    element: element.element_index,
+                    active: element.is_active,
+                    restriction: restriction.restriction.to_string(),
+                    bound: restriction.bound.clone(),
+                    value: restriction
+                        .value
+                        .clone()
+                        .unwrap_or_else(|| "<missing>".into()),
+                    valid: restriction.is_valid.to_string(),
+                });
+
+                let len = rows.len();
+                rows.extend(restrictions);
+
+                if rows.len() == len {
+                    rows.push(ElementRow {
+                        element: element.element_index,
+                        active: element.is_active,
+                        restriction: String::new(),
+                        bound: String::new(),
+                        value: String::new(),
+                        valid: String::new(),
+                    });
+                }
+            }
+
+            let active: Vec<bool> = rows.iter().map(|row| row.active).collect();
+
+            // Highlight the rows of active elements.
+            let format_active =
+                Modify::new(Rows::new(1..)).with(Format::positioned(|row, (i, _)| {
+                    if active[i - 1] {
+                        style(row).green().to_string()
+                    } else {
+                        style(row).dim().to_string()
+                    }
+                }));
+
+            println!(
+                "{}",
+                Table::new(rows).with(Style::modern()).with(format_active)
+            );
+
+            let rows = period.dimensions.iter().map(|dimension| DimensionRow {
+                dimension: dimension.dimension,
+                considered: dimension
+                    .considered
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                element: dimension.element_index.into(),
+            });
+
+            println!("{}", Table::new(rows).with(Style::modern()));
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Parser)]
 pub struct Analyze {
     #[command(flatten)]
@@ -300,24 +635,60 @@ impl Analyze {
             style(self.args.timezone).blue(),
         );
 
+        print_warnings(&report);
+
         let mut energy: PeriodTable<Kwh> = PeriodTable::new("Energy");
         let mut parking: PeriodTable<HoursDecimal> = PeriodTable::new("Parking time");
         let mut time: PeriodTable<HoursDecimal> = PeriodTable::new("Charging Time");
         let mut flat: PeriodTable<UnitDisplay> = PeriodTable::new("Flat");
+        let mut reservation_time: PeriodTable<HoursDecimal> = PeriodTable::new("Reservation time");
+        let mut reservation_flat: PeriodTable<UnitDisplay> = PeriodTable::new("Reservation flat");
 
         for period in report.periods.iter() {
             let start_time = period.start_date_time.with_timezone(&self.args.timezone);
 
-            energy.row(&period.dimensions.energy, start_time);
-            parking.row(&period.dimensions.parking_time, start_time);
-            time.row(&period.dimensions.time, start_time);
-            flat.row(&period.dimensions.flat, start_time);
+            energy.row(&period.dimensions.energy, start_time, &period.tariff_id);
+            parking.row(
+                &period.dimensions.parking_time,
+                start_time,
+                &period.tariff_id,
+            );
+            time.row(&period.dimensions.time, start_time, &period.tariff_id);
+            flat.row(&period.dimensions.flat, start_time, &period.tariff_id);
+            reservation_time.row(
+                &period.dimensions.reservation_time,
+                start_time,
+                &period.tariff_id,
+            );
+            reservation_flat.row(
+                &period.dimensions.reservation_flat,
+                start_time,
+                &period.tariff_id,
+            );
         }
 
         println!("{}", energy.into_table());
         println!("{}", parking.into_table());
         println!("{}", time.into_table());
         println!("{}", flat.into_table());
+        println!("{}", reservation_time.into_table());
+        println!("{}", reservation_flat.into_table());
+
+        if let Some(adjustment) = &report.price_adjustment {
+            let limit = match adjustment.limit {
+                PriceLimit::MinPrice => "min_price",
+                PriceLimit::MaxPrice => "max_price",
+            };
+
+            println!(
+                "Total cost limited by tariff `{}` of {} excl. VAT ({} incl. VAT), adjusted by {} excl. VAT ({} incl. VAT).\n",
+                style(limit).blue(),
+                report.currency.format(adjustment.bound.excl_vat),
+                report.currency.format(adjustment.bound.incl_vat),
+                report.currency.format(adjustment.amount.excl_vat),
+                report.currency.format(adjustment.amount.incl_vat),
+            );
+        }
 
         Ok(())
     }
@@ -336,15 +707,20 @@ impl<V: Display> PeriodTable<V> {
         }
     }
 
-    pub fn row<T>(&mut self, dim: &DimensionReport<T>, time: DateTime<Tz>)
+    pub fn row<T>(&mut self, dim: &DimensionReport<T>, time: DateTime<Tz>, tariff_id: &str)
     where
         T: Into<V> + Mul<Money, Output = Money> + Copy,
     {
         self.rows.push(PeriodComponent {
             time,
+            tariff_id: tariff_id.to_string(),
             price: dim.price.as_ref().map(|p| p.price).into(),
             volume: dim.volume.map(Into::into).into(),
             billed_volume: dim.billed_volume.map(Into::into).into(),
+            step_size: dim
+                .step_size
+                .map(|step_size| step_size.volume.into())
+                .into(),
             vat: dim.price.as_ref().and_then(|p| p.vat).into(),
             cost_excl_vat: dim.cost_excl_vat(),
             cost_incl_vat: dim.cost_incl_vat(),
@@ -368,6 +744,8 @@ impl<V: Display> PeriodTable<V> {
 pub struct PeriodComponent<V: Display> {
     #[tabled(rename = "Time", display_with = "format_time")]
     time: DateTime<Tz>,
+    #[tabled(rename = "Tariff")]
+    tariff_id: String,
     #[tabled(rename = "Price")]
     price: OptionDisplay<Money>,
     #[tabled(rename = "VAT")]
@@ -376,12 +754,29 @@ pub struct PeriodComponent<V: Display> {
     volume: OptionDisplay<V>,
     #[tabled(rename = "Billed volume")]
     billed_volume: OptionDisplay<V>,
+    #[tabled(rename = "Step size")]
+    step_size: OptionDisplay<V>,
     #[tabled(rename = "Cost excl. VAT")]
     cost_excl_vat: Money,
     #[tabled(rename = "Cost incl. VAT")]
     cost_incl_vat: Money,
 }
 
+fn print_warnings(report: &Report) {
+    if report.warnings.is_empty() {
+        return;
+    }
+
+    println!(
+        "The pricing produced {} warning(s):",
+        style(report.warnings.len()).yellow().bold()
+    );
+
+    for warning in &report.warnings {
+        println!("  - {warning}");
+    }
+}
+
 fn format_time(time: &DateTime<Tz>) -> String {
     time.format("%y-%m-%d %H:%M:%S").to_string()
 }
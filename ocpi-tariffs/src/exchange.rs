@@ -0,0 +1,121 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::types::number::Number;
+
+/// A table of exchange rates, keyed by currency pair and date. Used by the pricer to convert the
+/// prices of a tariff into the currency of the CDR.
+///
+/// ```ignore
+/// let mut rates = ExchangeRates::new();
+/// rates.insert("SEK", "EUR", NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(), "0.09".parse()?);
+///
+/// let pricer = Pricer::with_tariffs(cdr, tariffs, Tz::Europe__Stockholm).exchange_rates(rates);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeRates {
+    rates: HashMap<(String, String), BTreeMap<NaiveDate, Rate>>,
+}
+
+impl ExchangeRates {
+    /// Create an empty exchange rate table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert the `rate` to convert an amount in currency `from` into currency `to`, valid from
+    /// `date` until the date of the next rate of the same currency pair.
+    ///
+    /// Currencies are ISO 4217 codes, like the `currency` fields of CDRs and tariffs.
+    pub fn insert(&mut self, from: &str, to: &str, date: NaiveDate, rate: Rate) {
+        self.rates
+            .entry((from.to_string(), to.to_string()))
+            .or_default()
+            .insert(date, rate);
+    }
+
+    /// Find the rate to convert currency `from` into currency `to` that is valid on `date`. This
+    /// is the rate with the latest date on or before `date`.
+    pub fn rate(&self, from: &str, to: &str, date: NaiveDate) -> Option<ExchangeRate> {
+        let (&rate_date, &rate) = self
+            .rates
+            .get(&(from.to_string(), to.to_string()))?
+            .range(..=date)
+            .next_back()?;
+
+        Some(ExchangeRate {
+            from: from.to_string(),
+            to: to.to_string(),
+            date: rate_date,
+            rate,
+        })
+    }
+}
+
+/// An exchange rate that was used to convert the prices of a tariff into the currency of the
+/// CDR.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExchangeRate {
+    /// The currency of the tariff.
+    pub from: String,
+    /// The currency of the CDR.
+    pub to: String,
+    /// The date from which this rate is valid.
+    pub date: NaiveDate,
+    /// The amount in currency `to` of a single unit of currency `from`.
+    pub rate: Rate,
+}
+
+/// The amount in one currency of a single unit of another currency, which is always positive.
+///
+/// ```
+/// # use ocpi_tariffs::exchange::Rate;
+/// let rate: Rate = "0.09".parse().unwrap();
+/// assert_eq!(rate.to_string(), "0.09");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Rate(Number);
+
+impl From<Rate> for Number {
+    fn from(value: Rate) -> Self {
+        value.0
+    }
+}
+
+impl FromStr for Rate {
+    type Err = InvalidRate;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Decimal::from_str(s)
+            .ok()
+            .filter(|rate| rate.is_sign_positive() && !rate.is_zero())
+            .map(|rate| Self(rate.into()))
+            .ok_or_else(|| InvalidRate(s.to_string()))
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The value is not a positive decimal number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRate(pub String);
+
+impl fmt::Display for InvalidRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a positive decimal exchange rate", self.0)
+    }
+}
+
+impl std::error::Error for InvalidRate {}
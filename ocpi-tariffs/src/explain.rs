@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::types::period::DimensionType;
+
+/// Explains how the tariff elements were evaluated to price a single period of the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodTrace {
+    /// The start of the period.
+    pub start_date_time: DateTime<Utc>,
+    /// The index of the tariff that priced the period.
+    pub tariff_index: usize,
+    /// The id of the tariff that priced the period.
+    pub tariff_id: String,
+    /// The evaluation of the elements of the tariff in order, until active elements supplied the
+    /// price components of all dimensions.
+    pub elements: Vec<ElementTrace>,
+    /// For each dimension, the elements that were considered and the element that supplied the
+    /// price component.
+    pub dimensions: Vec<DimensionTrace>,
+}
+
+/// The evaluation of a single tariff element at the start of a period.
+#[derive(Debug, Clone, Serialize)]
+pub struct ElementTrace {
+    /// The index of the element in the tariff.
+    pub element_index: usize,
+    /// Whether the element describes the costs of a reservation, such an element only applies to
+    /// the periods of a reservation.
+    pub is_reservation: bool,
+    /// The outcome of each restriction of the element.
+    pub restrictions: Vec<RestrictionTrace>,
+    /// Whether the element is active during the period.
+    pub is_active: bool,
+}
+
+/// The outcome of a single restriction of a tariff element.
+#[derive(Debug, Clone, Serialize)]
+pub struct RestrictionTrace {
+    /// The name of the restriction in the tariff, for example `max_kwh`.
+    pub restriction: &'static str,
+    /// The bound of the restriction, for example `20.0000` for a `max_kwh` of 20 kWh.
+    pub bound: String,
+    /// The value of the period that is compared to the bound, for example the local time or the
+    /// total energy at the start of the period. `None` when the CDR doesn't provide the value, in
+    /// which case the restriction holds.
+    pub value: Option<String>,
+    /// Whether the restriction holds.
+    pub is_valid: bool,
+}
+
+/// The selection of the price component of a single dimension.
+#[derive(Debug, Clone, Serialize)]
+pub struct DimensionTrace {
+    /// The dimension that is priced.
+    pub dimension: DimensionType,
+    /// The indices of the elements that were considered in order, until an active element
+    /// supplied a price component.
+    pub considered: Vec<usize>,
+    /// The index of the element that supplied the price component, `None` when no active element
+    /// has a price component for this dimension.
+    pub element_index: Option<usize>,
+}
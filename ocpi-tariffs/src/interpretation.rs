@@ -0,0 +1,141 @@
+use chrono_tz::Tz;
+
+use crate::{
+    ocpi::{cdr::Cdr, tariff::OcpiTariff},
+    options::{
+        FlatFee, PricerOptions, RestrictionBound, RestrictionCheck, StepSizeScope, TimeStepSize,
+    },
+    pricer::{Pricer, Report},
+    types::{
+        money::Price,
+        rounding::{Rounding, RoundingMode, RoundingScope},
+    },
+    Error,
+};
+
+/// The result of pricing a CDR using a single reading of the ambiguous parts of the OCPI
+/// specification.
+pub struct Interpretation {
+    /// The options that were used to price the CDR.
+    pub options: PricerOptions,
+    /// The rounding policy that was used to price the CDR.
+    pub rounding: Rounding,
+    /// The report, or the error when the CDR can't be priced using these options.
+    pub report: Result<Report, Error>,
+}
+
+impl Interpretation {
+    /// Whether the totals of the report equal the totals of `cdr`, compared at the OCPI
+    /// specified amount of decimals. The optional totals are only compared when the CDR
+    /// contains them.
+    pub fn matches(&self, cdr: &Cdr) -> bool {
+        let Ok(report) = &self.report else {
+            return false;
+        };
+
+        let optional_price = |report: Price, cdr: Option<Price>| {
+            cdr.map(|cdr| report.with_scale() == cdr.with_scale())
+                .unwrap_or(true)
+        };
+
+        report.total_cost.with_scale() == cdr.total_cost.with_scale()
+            && report.total_energy.with_scale() == cdr.total_energy.with_scale()
+            && report.total_time.with_scale() == cdr.total_time.with_scale()
+            && cdr
+                .total_parking_time
+                .map(|time| report.total_parking_time.with_scale() == time.with_scale())
+                .unwrap_or(true)
+            && optional_price(report.total_fixed_cost, cdr.total_fixed_cost)
+            && optional_price(report.total_energy_cost, cdr.total_energy_cost)
+            && optional_price(report.total_time_cost, cdr.total_time_cost)
+            && optional_price(report.total_parking_cost, cdr.total_parking_cost)
+            && optional_price(report.total_reservation_cost, cdr.total_reservation_cost)
+    }
+}
+
+/// Price `cdr` using every combination of the [`PricerOptions`] and rounding modes, to find the
+/// reading that a partner used to calculate the totals of the CDR. Without `tariffs` the tariffs
+/// contained in the CDR are used.
+///
+/// Besides the unrounded totals, each rounding mode is applied to the totals of the session.
+///
+/// ```ignore
+/// let interpretations = interpret(&cdr, Some(&tariffs), Tz::Europe__Amsterdam);
+/// let matching = interpretations.iter().filter(|i| i.matches(&cdr));
+/// ```
+pub fn interpret(
+    cdr: &Cdr,
+    tariffs: Option<&[OcpiTariff]>,
+    local_timezone: Tz,
+) -> Vec<Interpretation> {
+    let mut interpretations = Vec::new();
+
+    for (options, rounding) in variants() {
+        let pricer = if let Some(tariffs) = tariffs {
+            Pricer::with_tariffs(cdr, tariffs, local_timezone)
+        } else {
+            Pricer::new(cdr, local_timezone)
+        };
+
+        let report = pricer.options(options).rounding(rounding).build_report();
+
+        interpretations.push(Interpretation {
+            options,
+            rounding,
+            report,
+        });
+    }
+
+    interpretations
+}
+
+/// All combinations of the options and rounding policies, starting with the strict reading.
+fn variants() -> Vec<(PricerOptions, Rounding)> {
+    // The default policy rounds the totals half up, the totals of a CDR may also keep their full
+    // precision.
+    let mut roundings = vec![
+        Rounding::default(),
+        Rounding {
+            scope: RoundingScope::Summary,
+            ..Rounding::default()
+        },
+    ];
+    roundings.extend(
+        [RoundingMode::HalfEven, RoundingMode::Down, RoundingMode::Up].map(|mode| Rounding {
+            mode,
+            scope: RoundingScope::Total,
+            ..Rounding::default()
+        }),
+    );
+
+    let mut variants = Vec::new();
+
+    for step_size_scope in [
+        StepSizeScope::Session,
+        StepSizeScope::Element,
+        StepSizeScope::LastElement,
+    ] {
+        for flat_fee in [FlatFee::OncePerSession, FlatFee::OncePerElementActivation] {
+            for restriction_check in [RestrictionCheck::Split, RestrictionCheck::PeriodStart] {
+                for restriction_bound in [RestrictionBound::Exclusive, RestrictionBound::Inclusive]
+                {
+                    for time_step_size in [TimeStepSize::UnlessParking, TimeStepSize::Always] {
+                        let options = PricerOptions {
+                            flat_fee,
+                            step_size_scope,
+                            time_step_size,
+                            restriction_check,
+                            restriction_bound,
+                        };
+
+                        for &rounding in &roundings {
+                            variants.push((options, rounding));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    variants
+}
@@ -3,18 +3,38 @@
 
 use std::fmt;
 
+use ocpi::tariff::TariffDimensionType;
+use types::period::DimensionType;
+use validation::CdrIssue;
+
+/// Module containing exchange rates to price sessions using tariffs in another currency.
+pub mod exchange;
+/// Module containing the trace of the evaluation of the tariff elements for each priced period.
+pub mod explain;
+/// Module containing the comparison of the readings of the OCPI specification for a single CDR.
+pub mod interpretation;
 /// OCPI specific structures for defining tariffs and charge sessions.
 pub mod ocpi;
+/// Module containing the options for the parts of the OCPI specification that are ambiguous.
+pub mod options;
 /// Module containing the functionality to price charge sessions with provided tariffs.
 pub mod pricer;
 
 mod restriction;
+/// Module containing the selection of the tariff that prices a charge session.
+pub mod selector;
 mod session;
 mod tariff;
 
 /// OCPI specific numeric types used for calculations, serializing and deserializing.
 pub mod types;
 
+/// Module containing the structural validation of charge detail records.
+pub mod validation;
+
+/// Module containing the warnings about suspicious input that can still be priced.
+pub mod warning;
+
 type Result<T> = std::result::Result<T, Error>;
 
 /// Possible errors when pricing a charge session.
@@ -25,12 +45,153 @@ pub enum Error {
     /// A valid tariff must have a start date time before the start of the session and a end date
     /// time after the start of the session.
     NoValidTariff,
+    /// The CDR contains structural issues. Only returned when the pricer is instructed to
+    /// validate the CDR before pricing.
+    InvalidCdr(Vec<CdrIssue>),
+    /// A numeric overflow occurred while calculating the volume of a dimension.
+    Overflow {
+        /// Index of the tariff that was used to price the period, `None` when the total volume of
+        /// the charging periods of the CDR overflowed.
+        tariff_index: Option<usize>,
+        /// Index of the period in the report, or of the charging period in the CDR when
+        /// `tariff_index` is `None`.
+        period_index: usize,
+        /// The dimension that overflowed.
+        dimension: DimensionType,
+    },
+    /// A dimension has no volume in a period, while a volume is required to price it.
+    MissingVolume {
+        /// Index of the tariff that was used to price the period.
+        tariff_index: usize,
+        /// Index of the period in the report.
+        period_index: usize,
+        /// The dimension without a volume.
+        dimension: DimensionType,
+    },
+    /// A charging period of the CDR starts before the previous period or after the session
+    /// stopped.
+    InconsistentCdr {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+    },
+    /// A charging period of the CDR contains a negative volume.
+    NegativeVolume {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+        /// The dimension with the negative volume.
+        dimension: DimensionType,
+    },
+    /// The currency of a tariff differs from the currency of the CDR, and no exchange rate between
+    /// these currencies was provided.
+    CurrencyMismatch {
+        /// Index of the tariff with the differing currency.
+        tariff_index: usize,
+        /// The currency of the tariff.
+        tariff_currency: String,
+        /// The currency of the CDR.
+        cdr_currency: String,
+    },
+    /// The currency of the CDR or of a tariff is not an ISO 4217 currency code.
+    UnknownCurrency {
+        /// Index of the tariff with the unknown currency, `None` for the currency of the CDR.
+        tariff_index: Option<usize>,
+        /// The unknown currency.
+        currency: String,
+    },
+    /// A tariff element contains a price component of a dimension that can't be priced by that
+    /// element. For example an `ENERGY` component in an element with a reservation restriction.
+    UnsupportedDimension {
+        /// Index of the tariff that contains the element.
+        tariff_index: usize,
+        /// Index of the element in the tariff.
+        element_index: usize,
+        /// The type of the unsupported price component.
+        dimension: TariffDimensionType,
+    },
 }
 
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("No valid tariff has been found in the list of provided tariffs")
+        match self {
+            Self::NoValidTariff => {
+                f.write_str("No valid tariff has been found in the list of provided tariffs")
+            }
+            Self::InvalidCdr(issues) => {
+                write!(f, "The CDR contains {} structural issue(s)", issues.len())?;
+
+                for issue in issues {
+                    write!(f, "\n  - {issue}")?;
+                }
+
+                Ok(())
+            }
+            Self::Overflow {
+                tariff_index: Some(tariff_index),
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Overflow while pricing dimension `{dimension}` in period {period_index} using tariff {tariff_index}"
+            ),
+            Self::Overflow {
+                tariff_index: None,
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Overflow while adding up dimension `{dimension}` in charging period {period_index} of the CDR"
+            ),
+            Self::MissingVolume {
+                tariff_index,
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Dimension `{dimension}` has no volume in period {period_index} priced using tariff {tariff_index}"
+            ),
+            Self::InconsistentCdr { period_index } => write!(
+                f,
+                "Charging period {period_index} of the CDR starts before the previous period or after the session stopped"
+            ),
+            Self::NegativeVolume {
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Charging period {period_index} of the CDR has a negative volume for dimension `{dimension}`"
+            ),
+            Self::CurrencyMismatch {
+                tariff_index,
+                tariff_currency,
+                cdr_currency,
+            } => write!(
+                f,
+                "The currency `{tariff_currency}` of tariff {tariff_index} differs from the currency `{cdr_currency}` of the CDR"
+            ),
+            Self::UnknownCurrency {
+                tariff_index: Some(tariff_index),
+                currency,
+            } => write!(
+                f,
+                "The currency `{currency}` of tariff {tariff_index} is not an ISO 4217 currency code"
+            ),
+            Self::UnknownCurrency {
+                tariff_index: None,
+                currency,
+            } => write!(
+                f,
+                "The currency `{currency}` of the CDR is not an ISO 4217 currency code"
+            ),
+            Self::UnsupportedDimension {
+                tariff_index,
+                element_index,
+                dimension,
+            } => write!(
+                f,
+                "Element {element_index} of tariff {tariff_index} contains an unsupported `{dimension}` price component"
+            ),
+        }
     }
 }
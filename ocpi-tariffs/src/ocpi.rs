@@ -1,5 +1,80 @@
+use std::{fmt, str::FromStr};
+
 /// OCPI specific structures for defining charge detail records.
 pub mod cdr;
 
 /// OCPI specific structures for defining tariffs.
 pub mod tariff;
+
+/// OCPI 2.1.1 structures, which can be converted from and into the OCPI 2.2.1 structures.
+pub mod v211;
+
+/// OCPI 3.0 structures, which are priced using [`Pricer::with_v3_tariffs`].
+///
+/// [`Pricer::with_v3_tariffs`]: crate::pricer::Pricer::with_v3_tariffs
+pub mod v3;
+
+/// A version of the OCPI specification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// OCPI 2.1.1
+    V211,
+    /// OCPI 2.2.1
+    #[default]
+    V221,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V211 => f.write_str("2.1.1"),
+            Self::V221 => f.write_str("2.2.1"),
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = UnsupportedVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "2.1.1" => Ok(Self::V211),
+            "2.2.1" => Ok(Self::V221),
+            _ => Err(UnsupportedVersion(s.to_string())),
+        }
+    }
+}
+
+/// The OCPI version is not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedVersion(pub String);
+
+impl fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "OCPI version `{}` is not supported, expected `2.1.1` or `2.2.1`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
+
+/// Deserialize a tariff of OCPI `version` from `json`, converting it into the OCPI 2.2.1
+/// structure that the pricer uses.
+pub fn tariff_from_str(json: &str, version: Version) -> serde_json::Result<tariff::OcpiTariff> {
+    match version {
+        Version::V211 => serde_json::from_str::<v211::tariff::OcpiTariff>(json).map(Into::into),
+        Version::V221 => serde_json::from_str(json),
+    }
+}
+
+/// Deserialize a CDR of OCPI `version` from `json`, converting it into the OCPI 2.2.1 structure
+/// that the pricer uses.
+pub fn cdr_from_str(json: &str, version: Version) -> serde_json::Result<cdr::Cdr> {
+    match version {
+        Version::V211 => serde_json::from_str::<v211::cdr::Cdr>(json).map(Into::into),
+        Version::V221 => serde_json::from_str(json),
+    }
+}
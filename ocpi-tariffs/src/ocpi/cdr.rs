@@ -1,22 +1,64 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use crate::ocpi::tariff::OcpiTariff;
 
 use crate::types::{
-    electricity::{Ampere, Kw, Kwh},
+    electricity::{Ampere, Kw, Kwh, Percentage},
     money::Price,
-    time::{DateTime, HoursDecimal},
+    time::{serialize_hours, serialize_optional_hours, DateTime, HoursDecimal},
 };
 
 /// The CDR object describes the Charging Session and its costs. How these costs are build up etc.
+///
+/// Fields that are not part of the OCPI specification are kept in `unknown_fields`, such that
+/// serializing a deserialized CDR results in the same JSON.
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Cdr {
+    /// ISO-3166 alpha-2 country code of the CPO that 'owns' this CDR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+
+    /// ID of the CPO that 'owns' this CDR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+
+    /// Uniquely identifies the CDR within the CPO’s platform.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
     /// Start timestamp of the charging session.
     pub start_date_time: DateTime,
 
     /// Stop timestamp of the charging session.
     pub stop_date_time: DateTime,
 
+    /// Unique ID of the session for which this CDR is sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+
+    /// Token used to start this charging session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cdr_token: Option<CdrToken>,
+
+    /// Method used for authentication.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_method: Option<AuthMethod>,
+
+    /// Reference to the authorization given by the eMSP.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization_reference: Option<String>,
+
+    /// Location where the charging session took place.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cdr_location: Option<CdrLocation>,
+
+    /// Identification of the Meter inside the Charge Point.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meter_id: Option<String>,
+
     /// Currency of the CDR in ISO 4217 Code.
     pub currency: String,
 
@@ -27,43 +69,406 @@ pub struct Cdr {
     /// more periods, where each period has a different relevant Tariff.
     pub charging_periods: Vec<OcpiChargingPeriod>,
 
+    /// Signed data that belongs to this charging session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_data: Option<SignedData>,
+
     /// Total cost of this transaction.
     pub total_cost: Price,
 
     /// Total cost of the flat dimension.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub total_fixed_cost: Option<Price>,
 
     /// Total energy charged, in kWh.
     pub total_energy: Kwh,
 
     /// Total cost related to the energy dimension.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub total_energy_cost: Option<Price>,
 
     /// Total time charging, in hours
+    #[serde(serialize_with = "serialize_hours")]
     pub total_time: HoursDecimal,
 
     /// Total cost related to the charging time dimension.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub total_time_cost: Option<Price>,
 
     /// Total time not charging, in hours
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_hours"
+    )]
     pub total_parking_time: Option<HoursDecimal>,
 
     /// Total cost related to the parking time dimension.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub total_parking_cost: Option<Price>,
 
     /// Total cost related to reservation time.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub total_reservation_cost: Option<Price>,
 
+    /// Optional remark, can be used to provide additional human readable information to the CDR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remark: Option<String>,
+
+    /// Reference to an invoice that contains this CDR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_reference_id: Option<String>,
+
+    /// When set to `true`, this is a Credit CDR, and the field `credit_reference_id` needs to be
+    /// set as well.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit: Option<bool>,
+
+    /// Is required to be set for a Credit CDR. This SHALL contain the `id` of the CDR for which
+    /// this is a Credit CDR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit_reference_id: Option<String>,
+
+    /// When set to `true`, this CDR is for a charging session using the home charger of the EV
+    /// Driver for which the energy cost needs to be financially compensated to the EV Driver.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home_charging_compensation: Option<bool>,
+
     /// Timestamp when this CDR was last updated
     pub last_updated: DateTime,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The token that was used to start a charging session.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CdrToken {
+    /// ISO-3166 alpha-2 country code of the MSP that 'owns' this Token.
+    pub country_code: String,
+
+    /// ID of the eMSP that 'owns' this Token.
+    pub party_id: String,
+
+    /// Unique ID by which this Token can be identified.
+    pub uid: String,
+
+    /// Type of the token.
+    #[serde(rename = "type")]
+    pub token_type: TokenType,
+
+    /// Uniquely identifies the EV driver contract token within the eMSP’s platform.
+    pub contract_id: String,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The type of a token.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TokenType {
+    /// One time use Token ID generated by a server (or App).
+    AdHocUser,
+    /// Token ID generated by a server (or App) to identify a user of an App.
+    AppUser,
+    /// Other type of token.
+    Other,
+    /// RFID Token.
+    Rfid,
+}
+
+/// The method used to authenticate a charging session.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuthMethod {
+    /// Authentication request has been sent to the eMSP.
+    AuthRequest,
+    /// Command like `START_SESSION` or `RESERVE_NOW` used to start the session.
+    Command,
+    /// Whitelist used for authentication, no request to the eMSP has been performed.
+    Whitelist,
+}
+
+/// The location and EVSE where a charging session took place.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CdrLocation {
+    /// Uniquely identifies the location within the CPO’s platform.
+    pub id: String,
+
+    /// Display name of the location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Street/block name and house number if available.
+    pub address: String,
+
+    /// City or town.
+    pub city: String,
+
+    /// Postal code of the location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+
+    /// State only to be used when relevant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+
+    /// ISO 3166-1 alpha-3 code for the country of this location.
+    pub country: String,
+
+    /// Coordinates of the location.
+    pub coordinates: GeoLocation,
+
+    /// Uniquely identifies the EVSE within the CPO’s platform.
+    pub evse_uid: String,
+
+    /// Compliant with the eMI3 standard EVSE ID.
+    pub evse_id: String,
+
+    /// Identifier of the connector within the EVSE.
+    pub connector_id: String,
+
+    /// The standard of the installed connector.
+    pub connector_standard: ConnectorType,
+
+    /// The format (socket/cable) of the installed connector.
+    pub connector_format: ConnectorFormat,
+
+    /// The power type of the installed connector.
+    pub connector_power_type: PowerType,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// A geographical location, the latitude and longitude are decimal degrees.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct GeoLocation {
+    /// Latitude of the point in decimal degree. Example: 50.770774.
+    pub latitude: String,
+
+    /// Longitude of the point in decimal degree. Example: -126.104965.
+    pub longitude: String,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The standard of a connector.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+pub enum ConnectorType {
+    /// The connector type is CHAdeMO, DC.
+    #[serde(rename = "CHADEMO")]
+    Chademo,
+    /// The ChaoJi connector. The new generation charging connector, harmonized between CHAdeMO
+    /// and GB/T. DC.
+    #[serde(rename = "CHAOJI")]
+    ChaoJi,
+    /// Standard/Domestic household, type "A", NEMA 1-15, 2 pins.
+    #[serde(rename = "DOMESTIC_A")]
+    DomesticA,
+    /// Standard/Domestic household, type "B", NEMA 5-15, 3 pins.
+    #[serde(rename = "DOMESTIC_B")]
+    DomesticB,
+    /// Standard/Domestic household, type "C", CEE 7/17, 2 pins.
+    #[serde(rename = "DOMESTIC_C")]
+    DomesticC,
+    /// Standard/Domestic household, type "D", 3 pin.
+    #[serde(rename = "DOMESTIC_D")]
+    DomesticD,
+    /// Standard/Domestic household, type "E", CEE 7/5 3 pins.
+    #[serde(rename = "DOMESTIC_E")]
+    DomesticE,
+    /// Standard/Domestic household, type "F", CEE 7/4, Schuko, 3 pins.
+    #[serde(rename = "DOMESTIC_F")]
+    DomesticF,
+    /// Standard/Domestic household, type "G", BS 1363, Commonwealth, 3 pins.
+    #[serde(rename = "DOMESTIC_G")]
+    DomesticG,
+    /// Standard/Domestic household, type "H", SI-32, 3 pins.
+    #[serde(rename = "DOMESTIC_H")]
+    DomesticH,
+    /// Standard/Domestic household, type "I", AS 3112, 3 pins.
+    #[serde(rename = "DOMESTIC_I")]
+    DomesticI,
+    /// Standard/Domestic household, type "J", SEV 1011, 3 pins.
+    #[serde(rename = "DOMESTIC_J")]
+    DomesticJ,
+    /// Standard/Domestic household, type "K", DS 60884-2-D1, 3 pins.
+    #[serde(rename = "DOMESTIC_K")]
+    DomesticK,
+    /// Standard/Domestic household, type "L", CEI 23-16-VII, 3 pins.
+    #[serde(rename = "DOMESTIC_L")]
+    DomesticL,
+    /// Standard/Domestic household, type "M", BS 546, 3 pins.
+    #[serde(rename = "DOMESTIC_M")]
+    DomesticM,
+    /// Standard/Domestic household, type "N", NBR 14136, 3 pins.
+    #[serde(rename = "DOMESTIC_N")]
+    DomesticN,
+    /// Standard/Domestic household, type "O", TIS 166-2549, 3 pins.
+    #[serde(rename = "DOMESTIC_O")]
+    DomesticO,
+    /// Guobiao GB/T 20234.2 AC socket/connector.
+    #[serde(rename = "GBT_AC")]
+    GbtAc,
+    /// Guobiao GB/T 20234.3 DC connector.
+    #[serde(rename = "GBT_DC")]
+    GbtDc,
+    /// IEC 60309-2 Industrial Connector single phase 16 amperes (usually blue).
+    #[serde(rename = "IEC_60309_2_single_16")]
+    Iec60309_2Single16,
+    /// IEC 60309-2 Industrial Connector three phases 16 amperes (usually red).
+    #[serde(rename = "IEC_60309_2_three_16")]
+    Iec60309_2Three16,
+    /// IEC 60309-2 Industrial Connector three phases 32 amperes (usually red).
+    #[serde(rename = "IEC_60309_2_three_32")]
+    Iec60309_2Three32,
+    /// IEC 60309-2 Industrial Connector three phases 64 amperes (usually red).
+    #[serde(rename = "IEC_60309_2_three_64")]
+    Iec60309_2Three64,
+    /// IEC 62196 Type 1 "SAE J1772".
+    #[serde(rename = "IEC_62196_T1")]
+    Iec62196T1,
+    /// Combo Type 1 based, DC.
+    #[serde(rename = "IEC_62196_T1_COMBO")]
+    Iec62196T1Combo,
+    /// IEC 62196 Type 2 "Mennekes".
+    #[serde(rename = "IEC_62196_T2")]
+    Iec62196T2,
+    /// Combo Type 2 based, DC.
+    #[serde(rename = "IEC_62196_T2_COMBO")]
+    Iec62196T2Combo,
+    /// IEC 62196 Type 3A.
+    #[serde(rename = "IEC_62196_T3A")]
+    Iec62196T3A,
+    /// IEC 62196 Type 3C "Scame".
+    #[serde(rename = "IEC_62196_T3C")]
+    Iec62196T3C,
+    /// NEMA 5-20, 3 pins.
+    #[serde(rename = "NEMA_5_20")]
+    Nema5_20,
+    /// NEMA 6-30, 3 pins.
+    #[serde(rename = "NEMA_6_30")]
+    Nema6_30,
+    /// NEMA 6-50, 3 pins.
+    #[serde(rename = "NEMA_6_50")]
+    Nema6_50,
+    /// NEMA 10-30, 3 pins.
+    #[serde(rename = "NEMA_10_30")]
+    Nema10_30,
+    /// NEMA 10-50, 3 pins.
+    #[serde(rename = "NEMA_10_50")]
+    Nema10_50,
+    /// NEMA 14-30, 3 pins, rating of 30 A.
+    #[serde(rename = "NEMA_14_30")]
+    Nema14_30,
+    /// NEMA 14-50, 3 pins, rating of 50 A.
+    #[serde(rename = "NEMA_14_50")]
+    Nema14_50,
+    /// On-board Bottom-up-Pantograph typically for bus charging.
+    #[serde(rename = "PANTOGRAPH_BOTTOM_UP")]
+    PantographBottomUp,
+    /// Off-board Top-down-Pantograph typically for bus charging.
+    #[serde(rename = "PANTOGRAPH_TOP_DOWN")]
+    PantographTopDown,
+    /// Tesla Connector "Roadster"-type (round, 4 pin).
+    #[serde(rename = "TESLA_R")]
+    TeslaR,
+    /// Tesla Connector "Model-S"-type (oval, 5 pin).
+    #[serde(rename = "TESLA_S")]
+    TeslaS,
+}
+
+/// The format of a connector.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConnectorFormat {
+    /// The connector is a socket; the EV user needs to bring a fitting plug.
+    Socket,
+    /// The connector is an attached cable; the EV users car needs to have a fitting inlet.
+    Cable,
+}
+
+/// The power type of a connector.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PowerType {
+    /// AC single phase.
+    #[serde(rename = "AC_1_PHASE")]
+    Ac1Phase,
+    /// AC two phases, only two of the three available phases connected.
+    #[serde(rename = "AC_2_PHASE")]
+    Ac2Phase,
+    /// AC two phases using split phase system.
+    #[serde(rename = "AC_2_PHASE_SPLIT")]
+    Ac2PhaseSplit,
+    /// AC three phases.
+    #[serde(rename = "AC_3_PHASE")]
+    Ac3Phase,
+    /// Direct Current.
+    Dc,
+}
+
+/// The signed meter values of a charging session, used to verify the CDR.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SignedData {
+    /// The name of the encoding used in the SignedData field.
+    pub encoding_method: String,
+
+    /// Version of the EncodingMethod (when applicable).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_method_version: Option<i64>,
+
+    /// Public key used to sign the data, base64 encoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+
+    /// One or more signed values.
+    pub signed_values: Vec<SignedValue>,
+
+    /// URL that can be shown to an EV driver.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// A single signed meter value.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SignedValue {
+    /// Nature of the value, in other words, the event this value belongs to.
+    pub nature: String,
+
+    /// The un-encoded string of data.
+    pub plain_data: String,
+
+    /// Blob of signed data, base64 encoded.
+    pub signed_data: String,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
 }
 
 /// The volume that has been consumed for a specific dimension during a charging period.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "type", content = "volume")]
 pub enum OcpiCdrDimension {
+    /// Average charging current during this period, in `A`.
+    Current(Ampere),
     /// Consumed energy in `kWh`.
     Energy(Kwh),
+    /// Total amount of energy, in `kWh`, exported from the EV to the grid during this period.
+    EnergyExport(Kwh),
+    /// Total amount of energy, in `kWh`, imported from the grid to the EV during this period.
+    EnergyImport(Kwh),
     /// The peak current, in 'A', during this period.
     MaxCurrent(Ampere),
     /// The lowest current, in `A`, during this period.
@@ -73,13 +478,89 @@ pub enum OcpiCdrDimension {
     /// The minimum power, in 'kW', reached during this period.
     MinPower(Kw),
     /// The parking time, in hours, consumed in this period.
+    #[serde(serialize_with = "serialize_hours")]
     ParkingTime(HoursDecimal),
     /// The reservation time, in hours, consumed in this period.
+    #[serde(serialize_with = "serialize_hours")]
     ReservationTime(HoursDecimal),
+    /// The state of charge of the battery of the EV at the end of this period, in percent.
+    StateOfCharge(Percentage),
     /// The charging time, in hours, consumed in this period.
+    #[serde(serialize_with = "serialize_hours")]
     Time(HoursDecimal),
 }
 
+impl OcpiCdrDimension {
+    /// The type of this dimension.
+    pub fn dimension_type(&self) -> CdrDimensionType {
+        match self {
+            Self::Current(_) => CdrDimensionType::Current,
+            Self::Energy(_) => CdrDimensionType::Energy,
+            Self::EnergyExport(_) => CdrDimensionType::EnergyExport,
+            Self::EnergyImport(_) => CdrDimensionType::EnergyImport,
+            Self::MaxCurrent(_) => CdrDimensionType::MaxCurrent,
+            Self::MinCurrent(_) => CdrDimensionType::MinCurrent,
+            Self::MaxPower(_) => CdrDimensionType::MaxPower,
+            Self::MinPower(_) => CdrDimensionType::MinPower,
+            Self::ParkingTime(_) => CdrDimensionType::ParkingTime,
+            Self::ReservationTime(_) => CdrDimensionType::ReservationTime,
+            Self::StateOfCharge(_) => CdrDimensionType::StateOfCharge,
+            Self::Time(_) => CdrDimensionType::Time,
+        }
+    }
+}
+
+/// The type of a dimension in a charging period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CdrDimensionType {
+    /// Average charging current during this period, in `A`.
+    Current,
+    /// Consumed energy in `kWh`.
+    Energy,
+    /// Total amount of energy, in `kWh`, exported from the EV to the grid during this period.
+    EnergyExport,
+    /// Total amount of energy, in `kWh`, imported from the grid to the EV during this period.
+    EnergyImport,
+    /// The peak current, in 'A', during this period.
+    MaxCurrent,
+    /// The lowest current, in `A`, during this period.
+    MinCurrent,
+    /// The maximum power, in 'kW', reached during this period.
+    MaxPower,
+    /// The minimum power, in 'kW', reached during this period.
+    MinPower,
+    /// The parking time, in hours, consumed in this period.
+    ParkingTime,
+    /// The reservation time, in hours, consumed in this period.
+    ReservationTime,
+    /// The state of charge of the battery of the EV at the end of this period, in percent.
+    StateOfCharge,
+    /// The charging time, in hours, consumed in this period.
+    Time,
+}
+
+impl fmt::Display for CdrDimensionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Current => "CURRENT",
+            Self::Energy => "ENERGY",
+            Self::EnergyExport => "ENERGY_EXPORT",
+            Self::EnergyImport => "ENERGY_IMPORT",
+            Self::MaxCurrent => "MAX_CURRENT",
+            Self::MinCurrent => "MIN_CURRENT",
+            Self::MaxPower => "MAX_POWER",
+            Self::MinPower => "MIN_POWER",
+            Self::ParkingTime => "PARKING_TIME",
+            Self::ReservationTime => "RESERVATION_TIME",
+            Self::StateOfCharge => "STATE_OF_CHARGE",
+            Self::Time => "TIME",
+        };
+
+        f.write_str(s)
+    }
+}
+
 /// A single charging period, containing a non empty list of charge dimensions.
 #[derive(Clone, Deserialize, Serialize)]
 pub struct OcpiChargingPeriod {
@@ -89,4 +570,12 @@ pub struct OcpiChargingPeriod {
 
     /// List of relevant values for this charging period
     pub dimensions: Vec<OcpiCdrDimension>,
+
+    /// Unique identifier of the tariff that is relevant for this charging period.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tariff_id: Option<String>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
 }
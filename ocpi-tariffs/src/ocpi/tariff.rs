@@ -1,37 +1,312 @@
 //! The Tariff object describes a tariff and its properties
 
+use std::fmt;
+
 use chrono::Weekday;
-use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 
 use crate::types::{
-    electricity::{Ampere, Kw, Kwh},
+    electricity::{Ampere, Kw, Kwh, Percentage},
     money::{Money, Price, Vat},
+    number::{deserialize_decimal, serialize_decimal},
     time::{DateTime, OcpiDate, OcpiTime, SecondsRound},
 };
 
+/// Implements `Deserialize` and `Serialize` of OCPI objects that keep their optional fields that
+/// are `null` or an empty list in `empty_fields`, using the implementations that are derived with
+/// `#[serde(remote = "Self")]`.
+macro_rules! keep_empty_fields {
+    ($($object:ty),*) => {$(
+        impl<'de> Deserialize<'de> for $object {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                use serde::de::Error;
+
+                let fields = Map::<String, Value>::deserialize(deserializer)?;
+                let empty_fields: Map<_, _> = fields
+                    .iter()
+                    .filter(|(_, value)| is_empty(value))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+
+                let mut object = Self::deserialize(Value::Object(fields)).map_err(D::Error::custom)?;
+                object.empty_fields = empty_fields
+                    .into_iter()
+                    .filter(|(name, _)| !object.unknown_fields.contains_key(name))
+                    .collect();
+
+                Ok(object)
+            }
+        }
+
+        impl Serialize for $object {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                use serde::ser::Error;
+
+                let mut value =
+                    Self::serialize(self, serde_json::value::Serializer).map_err(S::Error::custom)?;
+
+                if let Value::Object(fields) = &mut value {
+                    for (name, empty) in &self.empty_fields {
+                        fields.entry(name.clone()).or_insert_with(|| empty.clone());
+                    }
+                }
+
+                value.serialize(serializer)
+            }
+        }
+    )*};
+}
+
+keep_empty_fields!(
+    OcpiTariff,
+    EnergyMix,
+    OcpiPriceComponent,
+    OcpiTariffElement,
+    OcpiTariffRestriction
+);
+
+/// Whether `value` is `null` or an empty list.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(values) => values.is_empty(),
+        _ => false,
+    }
+}
+
 /// The Tariff object describes a tariff and its properties
+///
+/// Fields that are not part of the OCPI specification are kept in `unknown_fields` and optional
+/// fields that are `null` or an empty list in `empty_fields`, such that serializing a
+/// deserialized tariff results in the same JSON.
 #[derive(Clone, Deserialize, Serialize)]
+#[serde(remote = "Self")]
 pub struct OcpiTariff {
     /// Code designating in which country this country is active.
-    pub country_code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+
+    /// The ID of the party that owns this tariff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+
+    /// Uniquely identifies the tariff within the CPO’s platform.
+    pub id: String,
 
     /// Currency of this tariff, ISO 4217 Code
     pub currency: String,
 
+    /// Defines the type of the tariff.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub tariff_type: Option<TariffType>,
+
+    /// List of multi-language alternative tariff info texts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tariff_alt_text: Vec<DisplayText>,
+
+    /// URL to a web page that contains an explanation of the tariff information in human
+    /// readable form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tariff_alt_url: Option<String>,
+
     /// The minimum amount that this tariff will cost.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_price: Option<Price>,
 
     /// The maximum amount that this tariff will cost.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_price: Option<Price>,
 
     /// List of tariff elements
     pub elements: Vec<OcpiTariffElement>,
 
+    /// Details on the energy supplied with this tariff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_mix: Option<EnergyMix>,
+
     /// Start time when this tariff becomes active.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_date_time: Option<DateTime>,
 
     /// End time when this tariff becomes active.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub end_date_time: Option<DateTime>,
+
+    /// Timestamp when this tariff was last updated. Required by OCPI, but absent in tariffs
+    /// that are not yet stored, for example in the body of a PUT request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<DateTime>,
+
+    /// Optional fields that are `null` or an empty list. They deserialize like absent fields, but
+    /// are serialized as provided while the field is still unset or empty.
+    #[serde(skip)]
+    pub empty_fields: Map<String, Value>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The type of a tariff, used to select a tariff for a certain kind of session.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TariffType {
+    /// Used to describe that a tariff is valid when ad-hoc payment is used at the charge point.
+    AdHocPayment,
+    /// Used to describe that a tariff is valid when the charging preference `CHEAP` is set.
+    ProfileCheap,
+    /// Used to describe that a tariff is valid when the charging preference `FAST` is set.
+    ProfileFast,
+    /// Used to describe that a tariff is valid when the charging preference `GREEN` is set.
+    ProfileGreen,
+    /// Used to describe that a tariff is valid when using an RFID, without any charging
+    /// preference, or when the charging preference `REGULAR` is set.
+    Regular,
+}
+
+/// The charging preference of a session, used to select a tariff of the matching type.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProfileType {
+    /// Driver wants to use the cheapest charging profile possible.
+    Cheap,
+    /// Driver wants his EV charged as quickly as possible.
+    Fast,
+    /// Driver wants his EV charged with as much regenerative (green) energy as possible.
+    Green,
+    /// Driver does not have special preferences.
+    Regular,
+}
+
+impl From<ProfileType> for TariffType {
+    fn from(profile: ProfileType) -> Self {
+        match profile {
+            ProfileType::Cheap => Self::ProfileCheap,
+            ProfileType::Fast => Self::ProfileFast,
+            ProfileType::Green => Self::ProfileGreen,
+            ProfileType::Regular => Self::Regular,
+        }
+    }
+}
+
+/// A text in a specific language.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DisplayText {
+    /// Language code, ISO 639-1.
+    pub language: String,
+
+    /// Text to be displayed to an end user. No markup, html etc. allowed.
+    pub text: String,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The composition of the energy that is supplied.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(remote = "Self")]
+pub struct EnergyMix {
+    /// True if 100% from regenerative sources.
+    pub is_green_energy: bool,
+
+    /// Key-value pairs (enum + percentage) of energy sources of this location’s tariff.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub energy_sources: Vec<EnergySource>,
+
+    /// Key-value pairs (enum + percentage) of nuclear waste and CO2 exhaust of this location’s
+    /// tariff.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environ_impact: Vec<EnvironmentalImpact>,
+
+    /// Name of the energy supplier, delivering the energy for this location or tariff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_name: Option<String>,
+
+    /// Name of the energy suppliers product/tariff plan used at this location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_product_name: Option<String>,
+
+    /// Optional fields that are `null` or an empty list. They deserialize like absent fields, but
+    /// are serialized as provided while the field is still unset or empty.
+    #[serde(skip)]
+    pub empty_fields: Map<String, Value>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The share of a category of energy sources in an energy mix.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EnergySource {
+    /// The type of energy source.
+    pub source: EnergySourceCategory,
+
+    /// Percentage of this source (0-100) in the mix.
+    pub percentage: Percentage,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Categories of energy sources.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EnergySourceCategory {
+    /// Nuclear power sources.
+    Nuclear,
+    /// All kinds of fossil power sources.
+    GeneralFossil,
+    /// Fossil power from coal.
+    Coal,
+    /// Fossil power from gas.
+    Gas,
+    /// All kinds of regenerative power sources.
+    GeneralGreen,
+    /// Regenerative power from PV.
+    Solar,
+    /// Regenerative power from wind turbines.
+    Wind,
+    /// Regenerative power from water turbines.
+    Water,
+}
+
+/// The amount of an environmental impact of the supplied energy.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EnvironmentalImpact {
+    /// The environmental impact category of this value.
+    pub category: EnvironmentalImpactCategory,
+
+    /// Amount of this portion in g/kWh.
+    #[serde(
+        serialize_with = "serialize_decimal",
+        deserialize_with = "deserialize_decimal"
+    )]
+    pub amount: Decimal,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Categories of environmental impact values.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EnvironmentalImpactCategory {
+    /// Produced nuclear waste in grams per kilowatthour.
+    NuclearWaste,
+    /// Exhausted carbon dioxide in grams per kilowatthour.
+    CarbonDioxide,
 }
 
 /// Days of the week.
@@ -70,6 +345,7 @@ impl From<DayOfWeek> for Weekday {
 
 /// Component of a tariff price.
 #[derive(Clone, Deserialize, Serialize)]
+#[serde(remote = "Self")]
 pub struct OcpiPriceComponent {
     /// Type of tariff dimension
     #[serde(rename = "type")]
@@ -79,6 +355,7 @@ pub struct OcpiPriceComponent {
     pub price: Money,
 
     /// Optionally specify a VAT percentage for this component.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vat: Option<Vat>,
 
     /// Minimum amount to be billed. This unit will be billed in this step_size
@@ -86,16 +363,36 @@ pub struct OcpiPriceComponent {
     /// be billed in blocks of 5 minutes, so if 6 minutes is used, 10 minutes (2
     /// blocks of step_size) will be billed
     pub step_size: u64,
+
+    /// Optional fields that are `null` or an empty list. They deserialize like absent fields, but
+    /// are serialized as provided while the field is still unset or empty.
+    #[serde(skip)]
+    pub empty_fields: Map<String, Value>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
 }
 
 /// Describes part of a tariff
 #[derive(Clone, Deserialize, Serialize)]
+#[serde(remote = "Self")]
 pub struct OcpiTariffElement {
     /// List of price components that make up the pricing of this tariff
     pub price_components: Vec<OcpiPriceComponent>,
 
     /// Tariff restrictions object
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub restrictions: Option<OcpiTariffRestriction>,
+
+    /// Optional fields that are `null` or an empty list. They deserialize like absent fields, but
+    /// are serialized as provided while the field is still unset or empty.
+    #[serde(skip)]
+    pub empty_fields: Map<String, Value>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
 }
 
 /// Type of tariff component
@@ -112,53 +409,89 @@ pub enum TariffDimensionType {
     Time,
 }
 
+impl fmt::Display for TariffDimensionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Energy => "ENERGY",
+            Self::Flat => "FLAT",
+            Self::ParkingTime => "PARKING_TIME",
+            Self::Time => "TIME",
+        };
+
+        f.write_str(s)
+    }
+}
+
 /// Indicates when a tariff applies
 #[derive(Clone, Deserialize, Serialize)]
+#[serde(remote = "Self")]
 pub struct OcpiTariffRestriction {
     /// Start time of day, for example 13:30, valid from this time of the day.
     /// Must be in 24h format with leading zeros. Hour/Minute separator: “:” Regex
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_time: Option<OcpiTime>,
 
     /// End time of day, for example 19:45, valid until this
     /// time of the day. Same syntax as start_time
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub end_time: Option<OcpiTime>,
 
     /// Start date, for example: 2015-12-24, valid from this day
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_date: Option<OcpiDate>,
 
     /// End date, for example: 2015-12-27, valid until thisday (excluding this day)
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub end_date: Option<OcpiDate>,
 
     /// Minimum used energy in kWh, for example 20, valid from this amount of energy is used
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_kwh: Option<Kwh>,
 
     /// Maximum used energy in kWh, for example 50, valid until this amount of energy is used
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_kwh: Option<Kwh>,
 
     /// The minimum current in A.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_current: Option<Ampere>,
 
     /// The maximum current in A.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_current: Option<Ampere>,
 
     /// Minimum power in kW, for example 0, valid from this charging speed
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_power: Option<Kw>,
 
     /// Maximum power in kW, for example 20, valid up to this charging speed
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_power: Option<Kw>,
 
     /// Minimum duration in seconds, valid for a duration from x seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_duration: Option<SecondsRound>,
 
     /// Maximum duration in seconds, valid for a duration up to x seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_duration: Option<SecondsRound>,
 
     /// Which day(s) of the week this tariff is valid
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub day_of_week: Vec<DayOfWeek>,
 
     /// Whether this tariff applies for reservation.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reservation: Option<ReservationRestrictionType>,
+
+    /// Optional fields that are `null` or an empty list. They deserialize like absent fields, but
+    /// are serialized as provided while the field is still unset or empty.
+    #[serde(skip)]
+    pub empty_fields: Map<String, Value>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
 }
 
 /// The type of reservation a tariff applies to.
@@ -0,0 +1,5 @@
+/// OCPI 2.1.1 structures for defining charge detail records.
+pub mod cdr;
+
+/// OCPI 2.1.1 structures for defining tariffs.
+pub mod tariff;
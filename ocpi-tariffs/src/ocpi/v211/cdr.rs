@@ -0,0 +1,499 @@
+//! The OCPI 2.1.1 CDR object describes the charging session and its costs
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ocpi::cdr::{
+    self as v221, AuthMethod, CdrLocation, ConnectorFormat, ConnectorType, GeoLocation, PowerType,
+};
+use crate::ocpi::v211::tariff::OcpiTariff;
+use crate::types::{
+    electricity::{Ampere, Kw, Kwh},
+    money::{Money, Price},
+    number::{deserialize_decimal, serialize_decimal},
+    time::{serialize_hours, serialize_optional_hours, DateTime, HoursDecimal},
+};
+
+/// The OCPI 2.1.1 CDR object describes the charging session and its costs.
+///
+/// Compared to OCPI 2.2.1 the total cost is a single amount without VAT, the session is
+/// identified by an `auth_id` instead of a token and the location contains the full EVSE.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Cdr {
+    /// Uniquely identifies the CDR within the CPO’s platform.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Start timestamp of the charging session.
+    pub start_date_time: DateTime,
+
+    /// Stop timestamp of the charging session.
+    pub stop_date_time: DateTime,
+
+    /// Reference to a token, identified by the auth_id field of the Token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_id: Option<String>,
+
+    /// Method used for authentication.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_method: Option<AuthMethod>,
+
+    /// Location where the charging session took place, including only the relevant EVSE and
+    /// connector.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+
+    /// Identification of the Meter inside the Charge Point.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meter_id: Option<String>,
+
+    /// Currency of the CDR in ISO 4217 Code.
+    pub currency: String,
+
+    /// List of relevant tariffs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tariffs: Vec<OcpiTariff>,
+
+    /// List of charging periods that make up this charging session.
+    pub charging_periods: Vec<OcpiChargingPeriod>,
+
+    /// Total cost of this transaction.
+    pub total_cost: Money,
+
+    /// Total energy charged, in kWh.
+    pub total_energy: Kwh,
+
+    /// Total time charging, in hours
+    #[serde(serialize_with = "serialize_hours")]
+    pub total_time: HoursDecimal,
+
+    /// Total time not charging, in hours
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_hours"
+    )]
+    pub total_parking_time: Option<HoursDecimal>,
+
+    /// Optional remark, can be used to provide additional human readable information to the CDR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remark: Option<String>,
+
+    /// Timestamp when this CDR was last updated
+    pub last_updated: DateTime,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The location where a charging session took place.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Location {
+    /// Uniquely identifies the location within the CPO’s platform.
+    pub id: String,
+
+    /// Display name of the location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Street/block name and house number if available.
+    pub address: String,
+
+    /// City or town.
+    pub city: String,
+
+    /// Postal code of the location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+
+    /// ISO 3166-1 alpha-3 code for the country of this location.
+    pub country: String,
+
+    /// Coordinates of the location.
+    pub coordinates: GeoLocation,
+
+    /// The EVSE where the charging session took place.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evses: Vec<Evse>,
+
+    /// Fields that are not part of the OCPI specification, or that are not relevant for a CDR.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// An EVSE of a location.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Evse {
+    /// Uniquely identifies the EVSE within the CPO’s platform.
+    pub uid: String,
+
+    /// Compliant with the eMI3 standard EVSE ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evse_id: Option<String>,
+
+    /// The connector where the charging session took place.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub connectors: Vec<Connector>,
+
+    /// Fields that are not part of the OCPI specification, or that are not relevant for a CDR.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// A connector of an EVSE.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Connector {
+    /// Identifier of the connector within the EVSE.
+    pub id: String,
+
+    /// The standard of the installed connector.
+    pub standard: ConnectorType,
+
+    /// The format (socket/cable) of the installed connector.
+    pub format: ConnectorFormat,
+
+    /// The power type of the installed connector.
+    pub power_type: PowerType,
+
+    /// Fields that are not part of the OCPI specification, or that are not relevant for a CDR.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// The volume that has been consumed for a specific dimension during a charging period.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "type", content = "volume")]
+pub enum OcpiCdrDimension {
+    /// Consumed energy in `kWh`.
+    Energy(Kwh),
+    /// Flat fee, no unit.
+    Flat(
+        #[serde(
+            serialize_with = "serialize_decimal",
+            deserialize_with = "deserialize_decimal"
+        )]
+        Decimal,
+    ),
+    /// The peak current, in 'A', during this period.
+    MaxCurrent(Ampere),
+    /// The lowest current, in `A`, during this period.
+    MinCurrent(Ampere),
+    /// The maximum power, in 'kW', reached during this period.
+    MaxPower(Kw),
+    /// The minimum power, in 'kW', reached during this period.
+    MinPower(Kw),
+    /// The parking time, in hours, consumed in this period.
+    #[serde(serialize_with = "serialize_hours")]
+    ParkingTime(HoursDecimal),
+    /// The charging time, in hours, consumed in this period.
+    #[serde(serialize_with = "serialize_hours")]
+    Time(HoursDecimal),
+}
+
+/// A single charging period, containing a non empty list of charge dimensions.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiChargingPeriod {
+    /// Start timestamp of the charging period. This period ends when a next period starts, the
+    /// last period ends when the session ends
+    pub start_date_time: DateTime,
+
+    /// List of relevant values for this charging period
+    pub dimensions: Vec<OcpiCdrDimension>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Converts an OCPI 2.1.1 CDR into OCPI 2.2.1.
+///
+/// OCPI 2.1.1 has no VAT, the `total_cost` is used both excluding and including VAT, as if no
+/// VAT applies. The total cost including VAT of the resulting CDR is therefore not the amount
+/// that was charged when VAT applies. The
+/// `auth_id` has no OCPI 2.2.1 equivalent and is kept in the unknown fields. So is a location
+/// without an EVSE that has an `evse_id` and a connector.
+impl From<Cdr> for v221::Cdr {
+    fn from(cdr: Cdr) -> Self {
+        let mut unknown_fields = cdr.unknown_fields;
+
+        if let Some(auth_id) = cdr.auth_id {
+            unknown_fields.insert("auth_id".to_string(), auth_id.into());
+        }
+
+        let cdr_location = match cdr.location.map(Location::into_cdr_location) {
+            Some(Ok(location)) => Some(location),
+            Some(Err(location)) => {
+                if let Ok(location) = serde_json::to_value(location) {
+                    unknown_fields.insert("location".to_string(), location);
+                }
+                None
+            }
+            None => None,
+        };
+
+        Self {
+            country_code: None,
+            party_id: None,
+            id: cdr.id,
+            start_date_time: cdr.start_date_time,
+            stop_date_time: cdr.stop_date_time,
+            session_id: None,
+            cdr_token: None,
+            auth_method: cdr.auth_method,
+            authorization_reference: None,
+            cdr_location,
+            meter_id: cdr.meter_id,
+            currency: cdr.currency,
+            tariffs: cdr.tariffs.into_iter().map(Into::into).collect(),
+            charging_periods: cdr.charging_periods.into_iter().map(Into::into).collect(),
+            signed_data: None,
+            total_cost: Price {
+                excl_vat: cdr.total_cost,
+                incl_vat: cdr.total_cost,
+            },
+            total_fixed_cost: None,
+            total_energy: cdr.total_energy,
+            total_energy_cost: None,
+            total_time: cdr.total_time,
+            total_time_cost: None,
+            total_parking_time: cdr.total_parking_time,
+            total_parking_cost: None,
+            total_reservation_cost: None,
+            remark: cdr.remark,
+            invoice_reference_id: None,
+            credit: None,
+            credit_reference_id: None,
+            home_charging_compensation: None,
+            last_updated: cdr.last_updated,
+            unknown_fields,
+        }
+    }
+}
+
+/// Converts an OCPI 2.2.1 CDR into OCPI 2.1.1.
+///
+/// The `total_cost` excluding VAT is used as total cost. The `auth_id` is the `uid` of the
+/// token, and a location kept in the unknown fields is restored. Fields and dimensions that
+/// don't exist in OCPI 2.1.1 are dropped.
+impl From<v221::Cdr> for Cdr {
+    fn from(cdr: v221::Cdr) -> Self {
+        let mut unknown_fields = cdr.unknown_fields;
+
+        let auth_id = match cdr.cdr_token {
+            Some(token) => Some(token.uid),
+            None => match unknown_fields.remove("auth_id") {
+                Some(Value::String(auth_id)) => Some(auth_id),
+                Some(other) => {
+                    unknown_fields.insert("auth_id".to_string(), other);
+                    None
+                }
+                None => None,
+            },
+        };
+
+        let location = match cdr.cdr_location {
+            Some(location) => Some(location.into()),
+            None => match unknown_fields.remove("location") {
+                Some(location) => match serde_json::from_value(location.clone()) {
+                    Ok(location) => Some(location),
+                    Err(_e) => {
+                        unknown_fields.insert("location".to_string(), location);
+                        None
+                    }
+                },
+                None => None,
+            },
+        };
+
+        Self {
+            id: cdr.id,
+            start_date_time: cdr.start_date_time,
+            stop_date_time: cdr.stop_date_time,
+            auth_id,
+            auth_method: cdr.auth_method,
+            location,
+            meter_id: cdr.meter_id,
+            currency: cdr.currency,
+            tariffs: cdr.tariffs.into_iter().map(Into::into).collect(),
+            charging_periods: cdr.charging_periods.into_iter().map(Into::into).collect(),
+            total_cost: cdr.total_cost.excl_vat,
+            total_energy: cdr.total_energy,
+            total_time: cdr.total_time,
+            total_parking_time: cdr.total_parking_time,
+            remark: cdr.remark,
+            last_updated: cdr.last_updated,
+            unknown_fields,
+        }
+    }
+}
+
+impl Location {
+    /// The location of a CDR, using the first EVSE and its first connector.
+    ///
+    /// Returns the location itself when it has no EVSE with an `evse_id` and a connector, since
+    /// OCPI 2.2.1 requires those.
+    fn into_cdr_location(self) -> Result<CdrLocation, Box<Self>> {
+        let Some((evse_uid, evse_id, connector)) = self.evses.first().and_then(|evse| {
+            Some((
+                evse.uid.clone(),
+                evse.evse_id.clone()?,
+                evse.connectors.first()?.clone(),
+            ))
+        }) else {
+            return Err(Box::new(self));
+        };
+
+        Ok(CdrLocation {
+            id: self.id,
+            name: self.name,
+            address: self.address,
+            city: self.city,
+            postal_code: self.postal_code,
+            state: None,
+            country: self.country,
+            coordinates: self.coordinates,
+            evse_id,
+            evse_uid,
+            connector_id: connector.id,
+            connector_standard: connector.standard,
+            connector_format: connector.format,
+            connector_power_type: connector.power_type,
+            unknown_fields: self.unknown_fields,
+        })
+    }
+}
+
+impl From<CdrLocation> for Location {
+    fn from(location: CdrLocation) -> Self {
+        let connector = Connector {
+            id: location.connector_id,
+            standard: location.connector_standard,
+            format: location.connector_format,
+            power_type: location.connector_power_type,
+            unknown_fields: Map::new(),
+        };
+
+        let evse = Evse {
+            uid: location.evse_uid,
+            evse_id: Some(location.evse_id),
+            connectors: vec![connector],
+            unknown_fields: Map::new(),
+        };
+
+        Self {
+            id: location.id,
+            name: location.name,
+            address: location.address,
+            city: location.city,
+            postal_code: location.postal_code,
+            country: location.country,
+            coordinates: location.coordinates,
+            evses: vec![evse],
+            unknown_fields: location.unknown_fields,
+        }
+    }
+}
+
+/// The unknown field of an OCPI 2.2.1 charging period that keeps the volume of the OCPI 2.1.1
+/// `FLAT` dimension.
+pub(crate) const FLAT_VOLUME_FIELD: &str = "flat_volume";
+
+/// OCPI 2.2.1 charges flat fees per session and has no `FLAT` dimension, its volume is kept in the
+/// unknown field `flat_volume` but isn't priced, see [`Warning::DiscardedFlatVolume`].
+///
+/// [`Warning::DiscardedFlatVolume`]: crate::warning::Warning::DiscardedFlatVolume
+impl From<OcpiChargingPeriod> for v221::OcpiChargingPeriod {
+    fn from(period: OcpiChargingPeriod) -> Self {
+        let mut unknown_fields = period.unknown_fields;
+
+        let dimensions = period
+            .dimensions
+            .into_iter()
+            .filter_map(|dimension| match dimension {
+                OcpiCdrDimension::Energy(volume) => Some(v221::OcpiCdrDimension::Energy(volume)),
+                OcpiCdrDimension::Flat(volume) => {
+                    if let Ok(volume) = serialize_decimal(&volume, serde_json::value::Serializer) {
+                        unknown_fields.insert(FLAT_VOLUME_FIELD.to_string(), volume);
+                    }
+                    None
+                }
+                OcpiCdrDimension::MaxCurrent(volume) => {
+                    Some(v221::OcpiCdrDimension::MaxCurrent(volume))
+                }
+                OcpiCdrDimension::MinCurrent(volume) => {
+                    Some(v221::OcpiCdrDimension::MinCurrent(volume))
+                }
+                OcpiCdrDimension::MaxPower(volume) => {
+                    Some(v221::OcpiCdrDimension::MaxPower(volume))
+                }
+                OcpiCdrDimension::MinPower(volume) => {
+                    Some(v221::OcpiCdrDimension::MinPower(volume))
+                }
+                OcpiCdrDimension::ParkingTime(volume) => {
+                    Some(v221::OcpiCdrDimension::ParkingTime(volume))
+                }
+                OcpiCdrDimension::Time(volume) => Some(v221::OcpiCdrDimension::Time(volume)),
+            })
+            .collect();
+
+        Self {
+            start_date_time: period.start_date_time,
+            dimensions,
+            tariff_id: None,
+            unknown_fields,
+        }
+    }
+}
+
+/// The `tariff_id` and the dimensions that don't exist in OCPI 2.1.1 are dropped. A `FLAT`
+/// volume kept in the unknown fields is restored.
+impl From<v221::OcpiChargingPeriod> for OcpiChargingPeriod {
+    fn from(period: v221::OcpiChargingPeriod) -> Self {
+        let mut unknown_fields = period.unknown_fields;
+
+        let mut dimensions: Vec<_> = period
+            .dimensions
+            .into_iter()
+            .filter_map(|dimension| match dimension {
+                v221::OcpiCdrDimension::Energy(volume) => Some(OcpiCdrDimension::Energy(volume)),
+                v221::OcpiCdrDimension::MaxCurrent(volume) => {
+                    Some(OcpiCdrDimension::MaxCurrent(volume))
+                }
+                v221::OcpiCdrDimension::MinCurrent(volume) => {
+                    Some(OcpiCdrDimension::MinCurrent(volume))
+                }
+                v221::OcpiCdrDimension::MaxPower(volume) => {
+                    Some(OcpiCdrDimension::MaxPower(volume))
+                }
+                v221::OcpiCdrDimension::MinPower(volume) => {
+                    Some(OcpiCdrDimension::MinPower(volume))
+                }
+                v221::OcpiCdrDimension::ParkingTime(volume) => {
+                    Some(OcpiCdrDimension::ParkingTime(volume))
+                }
+                v221::OcpiCdrDimension::Time(volume) => Some(OcpiCdrDimension::Time(volume)),
+                v221::OcpiCdrDimension::Current(_)
+                | v221::OcpiCdrDimension::EnergyExport(_)
+                | v221::OcpiCdrDimension::EnergyImport(_)
+                | v221::OcpiCdrDimension::ReservationTime(_)
+                | v221::OcpiCdrDimension::StateOfCharge(_) => None,
+            })
+            .collect();
+
+        if let Some(volume) = unknown_fields.remove(FLAT_VOLUME_FIELD) {
+            match deserialize_decimal(&volume) {
+                Ok(volume) => dimensions.push(OcpiCdrDimension::Flat(volume)),
+                Err(_e) => {
+                    unknown_fields.insert(FLAT_VOLUME_FIELD.to_string(), volume);
+                }
+            }
+        }
+
+        Self {
+            start_date_time: period.start_date_time,
+            dimensions,
+            unknown_fields,
+        }
+    }
+}
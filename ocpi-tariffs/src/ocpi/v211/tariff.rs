@@ -0,0 +1,268 @@
+//! The OCPI 2.1.1 Tariff object describes a tariff and its properties
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ocpi::tariff::{self as v221, DayOfWeek, DisplayText, EnergyMix, TariffDimensionType};
+use crate::types::{
+    electricity::{Kw, Kwh},
+    money::Money,
+    time::{DateTime, OcpiDate, OcpiTime, SecondsRound},
+};
+
+/// The OCPI 2.1.1 Tariff object describes a tariff and its properties.
+///
+/// Compared to OCPI 2.2.1 a tariff has no owner, type, validity period or price limits.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiTariff {
+    /// Uniquely identifies the tariff within the CPO’s platform.
+    pub id: String,
+
+    /// Currency of this tariff, ISO 4217 Code
+    pub currency: String,
+
+    /// List of multi-language alternative tariff info texts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tariff_alt_text: Vec<DisplayText>,
+
+    /// URL to a web page that contains an explanation of the tariff information in human
+    /// readable form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tariff_alt_url: Option<String>,
+
+    /// List of tariff elements
+    pub elements: Vec<OcpiTariffElement>,
+
+    /// Details on the energy supplied with this tariff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_mix: Option<EnergyMix>,
+
+    /// Timestamp when this tariff was last updated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<DateTime>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Component of a tariff price. Prices have no VAT in OCPI 2.1.1.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiPriceComponent {
+    /// Type of tariff dimension
+    #[serde(rename = "type")]
+    pub component_type: TariffDimensionType,
+
+    /// Price per unit for this tariff dimension
+    pub price: Money,
+
+    /// Minimum amount to be billed. This unit will be billed in this step_size blocks.
+    pub step_size: u64,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Describes part of a tariff
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiTariffElement {
+    /// List of price components that make up the pricing of this tariff
+    pub price_components: Vec<OcpiPriceComponent>,
+
+    /// Tariff restrictions object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restrictions: Option<OcpiTariffRestriction>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Indicates when a tariff applies. OCPI 2.1.1 has no current or reservation restrictions.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiTariffRestriction {
+    /// Start time of day, for example 13:30, valid from this time of the day.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<OcpiTime>,
+
+    /// End time of day, for example 19:45, valid until this time of the day.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<OcpiTime>,
+
+    /// Start date, for example: 2015-12-24, valid from this day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<OcpiDate>,
+
+    /// End date, for example: 2015-12-27, valid until this day (excluding this day)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<OcpiDate>,
+
+    /// Minimum used energy in kWh, for example 20, valid from this amount of energy is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_kwh: Option<Kwh>,
+
+    /// Maximum used energy in kWh, for example 50, valid until this amount of energy is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_kwh: Option<Kwh>,
+
+    /// Minimum power in kW, for example 0, valid from this charging speed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_power: Option<Kw>,
+
+    /// Maximum power in kW, for example 20, valid up to this charging speed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_power: Option<Kw>,
+
+    /// Minimum duration in seconds, valid for a duration from x seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_duration: Option<SecondsRound>,
+
+    /// Maximum duration in seconds, valid for a duration up to x seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<SecondsRound>,
+
+    /// Which day(s) of the week this tariff is valid
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub day_of_week: Vec<DayOfWeek>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+impl From<OcpiTariff> for v221::OcpiTariff {
+    fn from(tariff: OcpiTariff) -> Self {
+        Self {
+            country_code: None,
+            party_id: None,
+            id: tariff.id,
+            currency: tariff.currency,
+            tariff_type: None,
+            tariff_alt_text: tariff.tariff_alt_text,
+            tariff_alt_url: tariff.tariff_alt_url,
+            min_price: None,
+            max_price: None,
+            elements: tariff.elements.into_iter().map(Into::into).collect(),
+            energy_mix: tariff.energy_mix,
+            start_date_time: None,
+            end_date_time: None,
+            last_updated: tariff.last_updated,
+            empty_fields: Map::new(),
+            unknown_fields: tariff.unknown_fields,
+        }
+    }
+}
+
+/// Converts an OCPI 2.2.1 tariff into OCPI 2.1.1. The owner, type, validity period and price
+/// limits of the tariff, the VAT of the price components and the current and reservation
+/// restrictions are dropped.
+impl From<v221::OcpiTariff> for OcpiTariff {
+    fn from(tariff: v221::OcpiTariff) -> Self {
+        Self {
+            id: tariff.id,
+            currency: tariff.currency,
+            tariff_alt_text: tariff.tariff_alt_text,
+            tariff_alt_url: tariff.tariff_alt_url,
+            elements: tariff.elements.into_iter().map(Into::into).collect(),
+            energy_mix: tariff.energy_mix,
+            last_updated: tariff.last_updated,
+            unknown_fields: tariff.unknown_fields,
+        }
+    }
+}
+
+impl From<OcpiTariffElement> for v221::OcpiTariffElement {
+    fn from(element: OcpiTariffElement) -> Self {
+        Self {
+            price_components: element
+                .price_components
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            restrictions: element.restrictions.map(Into::into),
+            empty_fields: Map::new(),
+            unknown_fields: element.unknown_fields,
+        }
+    }
+}
+
+impl From<v221::OcpiTariffElement> for OcpiTariffElement {
+    fn from(element: v221::OcpiTariffElement) -> Self {
+        Self {
+            price_components: element
+                .price_components
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            restrictions: element.restrictions.map(Into::into),
+            unknown_fields: element.unknown_fields,
+        }
+    }
+}
+
+impl From<OcpiPriceComponent> for v221::OcpiPriceComponent {
+    fn from(component: OcpiPriceComponent) -> Self {
+        Self {
+            component_type: component.component_type,
+            price: component.price,
+            vat: None,
+            step_size: component.step_size,
+            empty_fields: Map::new(),
+            unknown_fields: component.unknown_fields,
+        }
+    }
+}
+
+impl From<v221::OcpiPriceComponent> for OcpiPriceComponent {
+    fn from(component: v221::OcpiPriceComponent) -> Self {
+        Self {
+            component_type: component.component_type,
+            price: component.price,
+            step_size: component.step_size,
+            unknown_fields: component.unknown_fields,
+        }
+    }
+}
+
+impl From<OcpiTariffRestriction> for v221::OcpiTariffRestriction {
+    fn from(restriction: OcpiTariffRestriction) -> Self {
+        Self {
+            start_time: restriction.start_time,
+            end_time: restriction.end_time,
+            start_date: restriction.start_date,
+            end_date: restriction.end_date,
+            min_kwh: restriction.min_kwh,
+            max_kwh: restriction.max_kwh,
+            min_current: None,
+            max_current: None,
+            min_power: restriction.min_power,
+            max_power: restriction.max_power,
+            min_duration: restriction.min_duration,
+            max_duration: restriction.max_duration,
+            day_of_week: restriction.day_of_week,
+            reservation: None,
+            empty_fields: Map::new(),
+            unknown_fields: restriction.unknown_fields,
+        }
+    }
+}
+
+impl From<v221::OcpiTariffRestriction> for OcpiTariffRestriction {
+    fn from(restriction: v221::OcpiTariffRestriction) -> Self {
+        Self {
+            start_time: restriction.start_time,
+            end_time: restriction.end_time,
+            start_date: restriction.start_date,
+            end_date: restriction.end_date,
+            min_kwh: restriction.min_kwh,
+            max_kwh: restriction.max_kwh,
+            min_power: restriction.min_power,
+            max_power: restriction.max_power,
+            min_duration: restriction.min_duration,
+            max_duration: restriction.max_duration,
+            day_of_week: restriction.day_of_week,
+            unknown_fields: restriction.unknown_fields,
+        }
+    }
+}
@@ -0,0 +1,2 @@
+/// OCPI 3.0 structures for defining tariffs.
+pub mod tariff;
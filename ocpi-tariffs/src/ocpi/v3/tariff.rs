@@ -0,0 +1,285 @@
+//! The OCPI 3.0 Tariff object describes a tariff and its properties
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ocpi::tariff::{
+    self as v221, DisplayText, EnergyMix, OcpiTariffRestriction, TariffDimensionType, TariffType,
+};
+use crate::types::{
+    money::{self, Money, Vat},
+    time::DateTime,
+};
+use crate::warning::Warning;
+
+/// The OCPI 3.0 Tariff object describes a tariff and its properties.
+///
+/// Compared to OCPI 2.2.1 the prices of a tariff either include or exclude taxes, the price
+/// limits list their tax amounts and the step size of a price component applies to the volume
+/// that is priced by its tariff element. See [`Pricer::with_v3_tariffs`].
+///
+/// [`Pricer::with_v3_tariffs`]: crate::pricer::Pricer::with_v3_tariffs
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiTariff {
+    /// Code designating in which country this country is active.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+
+    /// The ID of the party that owns this tariff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+
+    /// Uniquely identifies the tariff within the CPO’s platform.
+    pub id: String,
+
+    /// Currency of this tariff, ISO 4217 Code
+    pub currency: String,
+
+    /// Defines the type of the tariff.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub tariff_type: Option<TariffType>,
+
+    /// List of multi-language alternative tariff info texts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tariff_alt_text: Vec<DisplayText>,
+
+    /// URL to a web page that contains an explanation of the tariff information in human
+    /// readable form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tariff_alt_url: Option<String>,
+
+    /// Whether the prices of the price components include taxes.
+    pub tax_included: TaxIncluded,
+
+    /// The minimum amount that this tariff will cost.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<Price>,
+
+    /// The maximum amount that this tariff will cost.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<Price>,
+
+    /// List of tariff elements
+    pub elements: Vec<OcpiTariffElement>,
+
+    /// Details on the energy supplied with this tariff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_mix: Option<EnergyMix>,
+
+    /// Start time when this tariff becomes active.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date_time: Option<DateTime>,
+
+    /// End time when this tariff becomes active.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date_time: Option<DateTime>,
+
+    /// Timestamp when this tariff was last updated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<DateTime>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Whether the prices of a tariff include taxes.
+#[derive(Debug, Copy, PartialEq, Eq, Clone, Hash, Deserialize, Serialize)]
+pub enum TaxIncluded {
+    /// The prices include taxes.
+    #[serde(rename = "YES")]
+    Yes,
+    /// The prices exclude taxes.
+    #[serde(rename = "NO")]
+    No,
+    /// No taxes apply to the prices.
+    #[serde(rename = "N/A")]
+    NotApplicable,
+}
+
+/// An amount before taxes, together with the taxes that apply to it.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Price {
+    /// The amount before taxes.
+    pub before_taxes: Money,
+
+    /// The taxes that apply to the amount.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub taxes: Vec<TaxAmount>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+impl Price {
+    /// The amount including all taxes.
+    pub fn after_taxes(&self) -> Money {
+        self.taxes
+            .iter()
+            .fold(self.before_taxes, |total, tax| total + tax.amount)
+    }
+}
+
+impl From<&Price> for money::Price {
+    fn from(price: &Price) -> Self {
+        Self {
+            excl_vat: price.before_taxes,
+            incl_vat: price.after_taxes(),
+        }
+    }
+}
+
+/// A single tax that applies to an amount.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct TaxAmount {
+    /// The name of the tax, for example `VAT`.
+    pub name: String,
+
+    /// The tax account number of the party that levies the tax.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_number: Option<String>,
+
+    /// The tax percentage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<Vat>,
+
+    /// The amount of tax.
+    pub amount: Money,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Component of a tariff price.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiPriceComponent {
+    /// Type of tariff dimension
+    #[serde(rename = "type")]
+    pub component_type: TariffDimensionType,
+
+    /// Price per unit for this tariff dimension, including or excluding taxes according to the
+    /// `tax_included` field of the tariff.
+    pub price: Money,
+
+    /// Optionally specify a VAT percentage for this component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vat: Option<Vat>,
+
+    /// Minimum amount to be billed. The volume that is priced by the tariff element of this
+    /// component is billed in blocks of this size.
+    pub step_size: u64,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Describes part of a tariff
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OcpiTariffElement {
+    /// List of price components that make up the pricing of this tariff
+    pub price_components: Vec<OcpiPriceComponent>,
+
+    /// Tariff restrictions object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restrictions: Option<OcpiTariffRestriction>,
+
+    /// Fields that are not part of the OCPI specification.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Converts an OCPI 3.0 tariff into the OCPI 2.2.1 tariff that is used for pricing. Prices that
+/// include taxes are converted to prices excluding VAT using the VAT of their component, the price
+/// limits keep their amounts before and after taxes. When no taxes apply the components have no
+/// VAT.
+impl From<&OcpiTariff> for v221::OcpiTariff {
+    fn from(tariff: &OcpiTariff) -> Self {
+        let elements = tariff
+            .elements
+            .iter()
+            .map(|element| v221::OcpiTariffElement {
+                price_components: element
+                    .price_components
+                    .iter()
+                    .map(|component| v221::OcpiPriceComponent {
+                        component_type: component.component_type,
+                        price: component.price_excl_vat(tariff.tax_included),
+                        vat: component.vat(tariff.tax_included),
+                        step_size: component.step_size,
+                        empty_fields: Map::new(),
+                        unknown_fields: component.unknown_fields.clone(),
+                    })
+                    .collect(),
+                restrictions: element.restrictions.clone(),
+                empty_fields: Map::new(),
+                unknown_fields: element.unknown_fields.clone(),
+            })
+            .collect();
+
+        Self {
+            country_code: tariff.country_code.clone(),
+            party_id: tariff.party_id.clone(),
+            id: tariff.id.clone(),
+            currency: tariff.currency.clone(),
+            tariff_type: tariff.tariff_type,
+            tariff_alt_text: tariff.tariff_alt_text.clone(),
+            tariff_alt_url: tariff.tariff_alt_url.clone(),
+            min_price: tariff.min_price.as_ref().map(Into::into),
+            max_price: tariff.max_price.as_ref().map(Into::into),
+            elements,
+            energy_mix: tariff.energy_mix.clone(),
+            start_date_time: tariff.start_date_time,
+            end_date_time: tariff.end_date_time,
+            last_updated: tariff.last_updated,
+            empty_fields: Map::new(),
+            unknown_fields: tariff.unknown_fields.clone(),
+        }
+    }
+}
+
+impl OcpiTariff {
+    /// The warnings about the taxes of this tariff, at `tariff_index`.
+    pub fn warnings(&self, tariff_index: usize) -> Vec<Warning> {
+        if self.tax_included != TaxIncluded::Yes {
+            return Vec::new();
+        }
+
+        self.elements
+            .iter()
+            .enumerate()
+            .flat_map(|(element_index, element)| {
+                element
+                    .price_components
+                    .iter()
+                    .filter(|component| component.vat.is_none())
+                    .map(move |component| Warning::MissingVat {
+                        tariff_index,
+                        element_index,
+                        dimension: component.component_type,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl OcpiPriceComponent {
+    /// The VAT percentage of this component, none when no taxes apply.
+    fn vat(&self, tax_included: TaxIncluded) -> Option<Vat> {
+        match tax_included {
+            TaxIncluded::NotApplicable => None,
+            TaxIncluded::Yes | TaxIncluded::No => self.vat,
+        }
+    }
+
+    /// The price of this component excluding VAT. A price that includes taxes but has no VAT
+    /// percentage is used as is, see [`Warning::MissingVat`].
+    fn price_excl_vat(&self, tax_included: TaxIncluded) -> Money {
+        match (tax_included, self.vat) {
+            (TaxIncluded::Yes, Some(vat)) => vat.exclude(self.price),
+            _ => self.price,
+        }
+    }
+}
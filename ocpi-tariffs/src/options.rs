@@ -0,0 +1,215 @@
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+
+/// Selects how the pricer reads the parts of the OCPI specification that are ambiguous.
+///
+/// The options are usually one of the named presets, see [`Profile`]. A preset can be adjusted
+/// for a partner, in which case the options report the [`Profile::Custom`] profile:
+///
+/// ```
+/// # use ocpi_tariffs::{options::{PricerOptions, Profile}, options::FlatFee};
+/// let options = PricerOptions {
+///     flat_fee: FlatFee::OncePerElementActivation,
+///     ..PricerOptions::strict()
+/// };
+///
+/// assert_eq!(options.profile(), Profile::Custom);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PricerOptions {
+    /// How often the `FLAT` price components of a tariff are charged.
+    pub flat_fee: FlatFee,
+    /// The volume that the step size of a price component is applied to.
+    pub step_size_scope: StepSizeScope,
+    /// Whether the step size of a `TIME` component applies when the session also has parking
+    /// time that is priced.
+    pub time_step_size: TimeStepSize,
+    /// When the restrictions of the tariff elements are checked.
+    pub restriction_check: RestrictionCheck,
+    /// Whether the bound of an `end_time`, `max_kwh` or `max_duration` restriction is still
+    /// within the restriction.
+    pub restriction_bound: RestrictionBound,
+}
+
+impl PricerOptions {
+    /// The reading of the OCPI 2.2.1 specification that the pricer uses by default.
+    pub fn strict() -> Self {
+        Self {
+            flat_fee: FlatFee::OncePerSession,
+            step_size_scope: StepSizeScope::Session,
+            time_step_size: TimeStepSize::UnlessParking,
+            restriction_check: RestrictionCheck::Split,
+            restriction_bound: RestrictionBound::Exclusive,
+        }
+    }
+
+    /// The strict reading with the step size applied per tariff element, as specified by
+    /// OCPI 3.0, which no longer skips the time step size when the session ends parking. Used to
+    /// price OCPI 3.0 tariffs.
+    pub fn ocpi_3() -> Self {
+        Self {
+            step_size_scope: StepSizeScope::Element,
+            time_step_size: TimeStepSize::Always,
+            ..Self::strict()
+        }
+    }
+
+    /// The reading of CPOs that evaluate the restrictions at the start of each charging period of
+    /// the CDR without splitting it, charge the flat fee each time an element becomes active and
+    /// always apply the step size of the charging time.
+    pub fn period_start() -> Self {
+        Self {
+            flat_fee: FlatFee::OncePerElementActivation,
+            step_size_scope: StepSizeScope::Session,
+            time_step_size: TimeStepSize::Always,
+            restriction_check: RestrictionCheck::PeriodStart,
+            restriction_bound: RestrictionBound::Exclusive,
+        }
+    }
+
+    /// The options of a named `profile`, the strict reading for [`Profile::Custom`].
+    pub fn from_profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Strict | Profile::Custom => Self::strict(),
+            Profile::Ocpi3 => Self::ocpi_3(),
+            Profile::PeriodStart => Self::period_start(),
+        }
+    }
+
+    /// The named profile that these options are equal to, or [`Profile::Custom`] when they differ
+    /// from all presets.
+    pub fn profile(&self) -> Profile {
+        [Profile::Strict, Profile::Ocpi3, Profile::PeriodStart]
+            .into_iter()
+            .find(|&profile| Self::from_profile(profile) == *self)
+            .unwrap_or(Profile::Custom)
+    }
+}
+
+impl Default for PricerOptions {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+/// The name of a preset of [`PricerOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Profile {
+    /// See [`PricerOptions::strict`].
+    Strict,
+    /// See [`PricerOptions::ocpi_3`].
+    Ocpi3,
+    /// See [`PricerOptions::period_start`].
+    PeriodStart,
+    /// The options differ from all presets.
+    Custom,
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Strict => f.write_str("strict"),
+            Self::Ocpi3 => f.write_str("ocpi-3"),
+            Self::PeriodStart => f.write_str("period-start"),
+            Self::Custom => f.write_str("custom"),
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = UnknownProfile;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(Self::Strict),
+            "ocpi-3" => Ok(Self::Ocpi3),
+            "period-start" => Ok(Self::PeriodStart),
+            _ => Err(UnknownProfile(s.to_string())),
+        }
+    }
+}
+
+/// The name is not one of the named profiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownProfile(pub String);
+
+impl fmt::Display for UnknownProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Profile `{}` is unknown, expected `strict`, `ocpi-3` or `period-start`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownProfile {}
+
+/// Describes how often the `FLAT` price components of a tariff are charged during a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FlatFee {
+    /// A flat fee is charged only once per session, in the first period that has an active
+    /// `FLAT` price component.
+    #[default]
+    OncePerSession,
+    /// A flat fee is charged each time the tariff element that supplies the `FLAT` price component
+    /// becomes active. For example when a start fee is part of a tariff element that is valid
+    /// only during the day, the start fee is charged again each day of the session.
+    OncePerElementActivation,
+}
+
+/// The volume that the step size of a price component is applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StepSizeScope {
+    /// The total volume of the session, the additional volume is billed in the last period in
+    /// which the dimension was priced. This is the OCPI 2.2.1 reading.
+    Session,
+    /// The volume priced by each tariff element, the additional volume is billed in the last
+    /// period priced by that element. This is the OCPI 3.0 reading.
+    Element,
+    /// The volume priced by the tariff element that was active last, the additional volume is
+    /// billed in the last period. The volumes priced by earlier elements are billed exactly.
+    LastElement,
+}
+
+/// Whether the step size of a `TIME` component applies when the session also has parking time
+/// that is priced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimeStepSize {
+    /// Only the step size of the `PARKING_TIME` component applies, since the session ends
+    /// parking.
+    UnlessParking,
+    /// The step sizes of both the `TIME` and the `PARKING_TIME` components apply.
+    Always,
+}
+
+/// When the restrictions of the tariff elements are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RestrictionCheck {
+    /// A charging period is split at each instant at which a restriction starts or stops to
+    /// apply, for example at the `end_time` or when `max_kwh` is reached.
+    Split,
+    /// The restrictions are checked at the start of each charging period of the CDR, which is
+    /// priced by the same elements until it ends. Periods are still split when another tariff
+    /// becomes valid.
+    PeriodStart,
+}
+
+/// Whether the bound of an `end_time`, `max_kwh` or `max_duration` restriction is still within
+/// the restriction. This decides whether an element applies to a period that starts exactly at
+/// such a bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RestrictionBound {
+    /// The bound is excluded, a restriction with an `end_time` of 15:00 no longer applies at
+    /// 15:00.
+    Exclusive,
+    /// The bound is included, a restriction with an `end_time` of 15:00 still applies at 15:00.
+    Inclusive,
+}
@@ -1,16 +1,29 @@
 use std::ops::Mul;
 
 use crate::{
-    ocpi::{cdr::Cdr, tariff::OcpiTariff},
+    exchange::{ExchangeRate, ExchangeRates},
+    explain::PeriodTrace,
+    ocpi::{
+        cdr::{Cdr, TokenType},
+        tariff::{OcpiTariff, ProfileType, TariffType},
+        v3,
+    },
+    options::{FlatFee, PricerOptions, Profile, RestrictionCheck, StepSizeScope, TimeStepSize},
+    selector::{DefaultSelector, SelectionContext, TariffCandidate, TariffSelector},
     session::ChargeSession,
-    session::{ChargePeriod, PeriodData},
-    tariff::{PriceComponent, PriceComponents, Tariffs},
+    session::{ChargePeriod, PeriodData, SplitPoint},
+    tariff::{PriceComponent, PriceComponents, Tariff, Tariffs},
     types::{
-        electricity::Kwh,
-        money::{Money, Price},
+        currency::{Currency, UnknownCurrency},
+        electricity::{Ampere, Kw, Kwh, Percentage},
+        money::{Money, Price, Vat},
         number::Number,
+        period::{DimensionType, PeriodSplit},
+        rounding::{Rounding, RoundingScope},
         time::HoursDecimal,
     },
+    validation::{check_cdr, validate_cdr},
+    warning::{unused_volumes, Warning},
     Error, Result,
 };
 
@@ -34,8 +47,18 @@ use serde::Serialize;
 /// let report = pricer.build_report();
 /// ```
 pub struct Pricer {
+    cdr: Cdr,
     session: ChargeSession,
     tariffs: Tariffs,
+    options: PricerOptions,
+    validate_cdr: bool,
+    exchange_rates: ExchangeRates,
+    charging_profile: Option<ProfileType>,
+    selector: Box<dyn TariffSelector>,
+    rounding: Rounding,
+    explain: bool,
+    /// Warnings about the provided tariffs that are found before they are converted.
+    tariff_warnings: Vec<Warning>,
 }
 
 impl Pricer {
@@ -43,8 +66,17 @@ impl Pricer {
     /// Provide the `local_timezone` of the area where this charge session was priced.
     pub fn new(cdr: &Cdr, local_timezone: Tz) -> Self {
         Self {
+            cdr: cdr.clone(),
             session: ChargeSession::new(cdr, local_timezone),
             tariffs: Tariffs::new(&cdr.tariffs),
+            options: PricerOptions::default(),
+            validate_cdr: false,
+            exchange_rates: ExchangeRates::default(),
+            charging_profile: None,
+            rounding: Rounding::default(),
+            selector: Box::new(DefaultSelector),
+            explain: false,
+            tariff_warnings: Vec::new(),
         }
     }
 
@@ -52,84 +84,507 @@ impl Pricer {
     /// Provide the `local_timezone` of the area where this charge session was priced.
     pub fn with_tariffs(cdr: &Cdr, tariffs: &[OcpiTariff], local_timezone: Tz) -> Self {
         Self {
+            cdr: cdr.clone(),
             session: ChargeSession::new(cdr, local_timezone),
             tariffs: Tariffs::new(tariffs),
+            options: PricerOptions::default(),
+            validate_cdr: false,
+            exchange_rates: ExchangeRates::default(),
+            charging_profile: None,
+            rounding: Rounding::default(),
+            selector: Box::new(DefaultSelector),
+            explain: false,
+            tariff_warnings: Vec::new(),
+        }
+    }
+
+    /// Instantiate the pricer with a `Cdr` and a slice that contains at least one OCPI 3.0
+    /// tariff. Provide the `local_timezone` of the area where this charge session was priced.
+    ///
+    /// Prices that include taxes are converted to prices excluding VAT, and the step size of a
+    /// price component is applied to the volume that is priced by its tariff element instead of
+    /// the total volume of the session. The result is the same [`Report`] as for OCPI 2.2.1. A
+    /// price that includes taxes without a VAT percentage is reported as [`Warning::MissingVat`].
+    ///
+    /// The session is priced using the [`PricerOptions::ocpi_3`] reading, which is replaced by
+    /// [`Pricer::options`].
+    pub fn with_v3_tariffs(
+        cdr: &Cdr,
+        tariffs: &[v3::tariff::OcpiTariff],
+        local_timezone: Tz,
+    ) -> Self {
+        let tariff_warnings = tariffs
+            .iter()
+            .enumerate()
+            .flat_map(|(tariff_index, tariff)| tariff.warnings(tariff_index))
+            .collect();
+        let tariffs: Vec<OcpiTariff> = tariffs.iter().map(Into::into).collect();
+
+        Self {
+            tariff_warnings,
+            ..Self::with_tariffs(cdr, &tariffs, local_timezone).options(PricerOptions::ocpi_3())
+        }
+    }
+
+    /// Specify how the ambiguous parts of the OCPI specification are read, by default the
+    /// [`PricerOptions::strict`] reading is used. The options are recorded in the report.
+    ///
+    /// The options replace all options that were set before, including the
+    /// [`PricerOptions::ocpi_3`] reading of [`Pricer::with_v3_tariffs`]. To change a single option
+    /// of that reading, start from it:
+    ///
+    /// ```ignore
+    /// let pricer = Pricer::with_v3_tariffs(cdr, tariffs, Tz::Europe__Amsterdam)
+    ///     .options(PricerOptions {
+    ///         time_step_size: TimeStepSize::Always,
+    ///         ..PricerOptions::ocpi_3()
+    ///     });
+    /// ```
+    pub fn options(mut self, options: PricerOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Specify how often the `FLAT` price components should be charged during the session. By
+    /// default a flat fee is charged once per session.
+    #[deprecated(note = "set `PricerOptions::flat_fee` using `Pricer::options`")]
+    pub fn flat_fee(mut self, flat_fee: FlatFee) -> Self {
+        self.options.flat_fee = flat_fee;
+        self
+    }
+
+    /// Validate the structure of the CDR before pricing, `build_report` returns
+    /// [`Error::InvalidCdr`] when any issue is found. By default the CDR is not validated, see
+    /// [`validate_cdr`] to validate a CDR on its own.
+    pub fn validate_cdr(mut self, validate_cdr: bool) -> Self {
+        self.validate_cdr = validate_cdr;
+        self
+    }
+
+    /// Provide the exchange rates to convert the prices of tariffs that use another currency than
+    /// the CDR. Without a matching rate `build_report` returns [`Error::CurrencyMismatch`]. The
+    /// rate that is valid on the (UTC) start date of the session is used.
+    pub fn exchange_rates(mut self, exchange_rates: ExchangeRates) -> Self {
+        self.exchange_rates = exchange_rates;
+        self
+    }
+
+    /// Specify the charging preference of the session. A tariff of the matching `PROFILE_*` type
+    /// is selected when available. Sessions started with an ad-hoc token select an
+    /// `AD_HOC_PAYMENT` tariff instead.
+    pub fn charging_profile(mut self, profile: ProfileType) -> Self {
+        self.charging_profile = Some(profile);
+        self
+    }
+
+    /// Specify how the tariff that prices the session is chosen, by default the
+    /// [`DefaultSelector`] is used.
+    pub fn tariff_selector(mut self, selector: impl TariffSelector + 'static) -> Self {
+        self.selector = Box::new(selector);
+        self
+    }
+
+    /// Specify how the amounts and volumes of the report are rounded, by default the totals and
+    /// the tax summary are rounded to the minor units of the currency. The policy is recorded in
+    /// the report.
+    pub fn rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Record in the report how the tariff elements were evaluated for each period, see
+    /// [`Report::trace`]. By default no trace is recorded.
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// The type of tariff that this session prefers.
+    fn preferred_tariff_type(&self) -> TariffType {
+        let is_ad_hoc = self
+            .cdr
+            .cdr_token
+            .as_ref()
+            .map(|token| token.token_type == TokenType::AdHocUser)
+            .unwrap_or(false);
+
+        if is_ad_hoc {
+            TariffType::AdHocPayment
+        } else {
+            self.charging_profile
+                .map(Into::into)
+                .unwrap_or(TariffType::Regular)
         }
     }
 
-    /// Attempt to apply the first found valid tariff the charge session and build a report
-    /// containing the results.
+    /// Check that `tariff` can be used to price the session, and find the exchange rate to
+    /// convert its prices into the currency of the CDR when the currencies differ.
+    fn check_tariff(&self, tariff_index: usize, tariff: &Tariff) -> Result<Option<ExchangeRate>> {
+        tariff.check(tariff_index)?;
+
+        if tariff.currency == self.session.currency {
+            return Ok(None);
+        }
+
+        let date = self.session.start_date_time.date_naive();
+
+        self.exchange_rates
+            .rate(&tariff.currency, &self.session.currency, date)
+            .map(Some)
+            .ok_or_else(|| Error::CurrencyMismatch {
+                tariff_index,
+                tariff_currency: tariff.currency.clone(),
+                cdr_currency: self.session.currency.clone(),
+            })
+    }
+
+    /// Find the tariff that is referenced by the `tariff_id` of `period`, if any.
+    fn tariff_by_id(&self, period: &ChargePeriod) -> Option<(usize, &Tariff)> {
+        period
+            .tariff_id
+            .as_deref()
+            .and_then(|id| self.resolve_tariff_id(id))
+    }
+
+    /// Find the tariff with identifier `id` of the CPO that owns the CDR.
+    fn resolve_tariff_id(&self, id: &str) -> Option<(usize, &Tariff)> {
+        self.tariffs.tariff_by_id(
+            id,
+            self.cdr.country_code.as_deref(),
+            self.cdr.party_id.as_deref(),
+        )
+    }
+
+    /// Warn about the charging periods of the CDR that reference a tariff that isn't provided.
+    fn unknown_tariffs(&self) -> impl Iterator<Item = Warning> + '_ {
+        self.cdr
+            .charging_periods
+            .iter()
+            .enumerate()
+            .filter_map(|(period_index, period)| {
+                let tariff_id = period.tariff_id.as_deref()?;
+
+                self.resolve_tariff_id(tariff_id)
+                    .is_none()
+                    .then(|| Warning::UnknownTariff {
+                        period_index,
+                        tariff_id: tariff_id.to_owned(),
+                    })
+            })
+    }
+
+    /// Attempt to apply the valid tariffs to the charge session and build a report containing the
+    /// results.
+    ///
+    /// A charging period that has a `tariff_id` is priced using the tariff with that id. Other
+    /// periods are priced using the tariff that is valid at the start of the session until
+    /// another tariff becomes valid. Charging periods that contain such a tariff change are split
+    /// at the instant that the other tariff becomes valid.
+    ///
+    /// Returns an error when the session can't be priced, for example because the CDR is
+    /// inconsistent or a tariff uses another currency than the CDR without a provided exchange
+    /// rate.
     pub fn build_report(&self) -> Result<Report> {
-        let (tariff_index, tariff) = self
-            .tariffs
-            .active_tariff(self.session.start_date_time)
+        if self.validate_cdr {
+            let issues = validate_cdr(&self.cdr);
+
+            if !issues.is_empty() {
+                return Err(Error::InvalidCdr(issues));
+            }
+        }
+
+        check_cdr(&self.cdr)?;
+        self.session.check()?;
+
+        let currency: Currency =
+            self.session
+                .currency
+                .parse()
+                .map_err(|UnknownCurrency(currency)| Error::UnknownCurrency {
+                    tariff_index: None,
+                    currency,
+                })?;
+
+        let context = SelectionContext {
+            date_time: self.session.start_date_time,
+            preferred_type: self.preferred_tariff_type(),
+            token: self.cdr.cdr_token.as_ref(),
+        };
+        let context_at = |date_time| SelectionContext {
+            date_time,
+            ..context
+        };
+
+        let selector = self.selector.as_ref();
+        let tariff_selection = self.tariffs.candidates(selector, &context);
+
+        let (mut tariff_index, mut tariff) = self
+            .session
+            .periods
+            .first()
+            .and_then(|period| self.tariff_by_id(period))
+            .or_else(|| self.tariffs.active_tariff(selector, &context))
             .ok_or(Error::NoValidTariff)?;
 
+        let mut exchange_rate = self.check_tariff(tariff_index, tariff)?;
+        let mut exchange_rates: Vec<ExchangeRate> = exchange_rate.iter().cloned().collect();
+
+        // The price limits of the tariff that is valid at the start of the session apply.
+        let session_tariff = tariff;
+        let session_rate = exchange_rate.as_ref().map(|rate| Number::from(rate.rate));
+
+        // Split the periods such that a single tariff and set of active tariff elements applies
+        // during each period.
+        let mut split_tariff = tariff;
+
+        let charge_periods = self.session.split_periods(|period| {
+            let start_time = period.start_instant.date_time;
+            let by_id = self.tariff_by_id(period);
+
+            if let Some((_, active)) = by_id.or_else(|| {
+                self.tariffs
+                    .active_tariff(selector, &context_at(start_time))
+            }) {
+                split_tariff = active;
+            }
+
+            // A period that references a tariff is priced using that tariff until it ends.
+            let tariff_change = if by_id.is_some() {
+                None
+            } else {
+                self.tariffs
+                    .next_tariff_change(
+                        selector,
+                        &context_at(start_time),
+                        period.end_instant.date_time,
+                    )
+                    .map(|date_time| (SplitPoint::Instant(date_time), PeriodSplit::TariffValidity))
+            };
+
+            let restriction_boundary = match self.options.restriction_check {
+                RestrictionCheck::Split => {
+                    split_tariff.next_restriction_boundary(period, self.options.restriction_bound)
+                }
+                RestrictionCheck::PeriodStart => None,
+            };
+
+            [tariff_change, restriction_boundary]
+                .into_iter()
+                .flatten()
+                .filter_map(|(point, reason)| Some((period.fraction(point)?, point, reason)))
+                .min_by_key(|&(fraction, _, _)| fraction)
+                .map(|(_, point, reason)| (point, reason))
+        })?;
+
         let mut periods = Vec::new();
         let mut step_size = StepSize::new();
+        let mut trace = Vec::new();
+        let mut warnings = unused_volumes(&self.cdr);
+        warnings.extend(self.unknown_tariffs());
+        warnings.extend(self.tariff_warnings.iter().cloned());
+        let mut warned_tariffs = Vec::new();
+        let mut untariffed_volumes = Vec::new();
 
         let mut total_energy = Kwh::zero();
         let mut total_charging_time = HoursDecimal::zero();
         let mut total_parking_time = HoursDecimal::zero();
+        let mut total_reservation_time = HoursDecimal::zero();
+
+        for (index, period) in charge_periods.iter().enumerate() {
+            // When no tariff is valid anymore, the last valid tariff remains active.
+            let active = self.tariff_by_id(period).or_else(|| {
+                self.tariffs
+                    .active_tariff(selector, &context_at(period.start_instant.date_time))
+            });
 
-        for (index, period) in self.session.periods.iter().enumerate() {
-            let components = tariff.active_components(period);
+            if let Some((index, active)) = active {
+                if index != tariff_index {
+                    exchange_rate = self.check_tariff(index, active)?;
 
-            step_size.update(index, &components, period);
+                    if let Some(rate) = &exchange_rate {
+                        if !exchange_rates.contains(rate) {
+                            exchange_rates.push(rate.clone());
+                        }
+                    }
+                }
+
+                tariff_index = index;
+                tariff = active;
+            }
+
+            let mut period_trace = self.explain.then(|| PeriodTrace {
+                start_date_time: period.start_instant.date_time,
+                tariff_index,
+                tariff_id: tariff.id.clone(),
+                elements: Vec::new(),
+                dimensions: Vec::new(),
+            });
+
+            let mut components = tariff.active_components(
+                period,
+                self.options.restriction_bound,
+                period_trace.as_mut(),
+            );
+
+            trace.extend(period_trace);
+
+            if let Some(rate) = &exchange_rate {
+                components.convert(rate.rate.into());
+            }
+
+            step_size.update(index, tariff_index, &components, period);
 
             let dimensions = Dimensions::new(components, &period.period_data);
 
-            total_charging_time.0 = total_charging_time.0
-                + dimensions
-                    .time
-                    .volume
-                    .map(|hms| hms.0)
-                    .unwrap_or_else(Duration::zero);
+            if !warned_tariffs.contains(&tariff_index) {
+                warned_tariffs.push(tariff_index);
+                warnings.extend(tariff.warnings(tariff_index));
+            }
+
+            for dimension in dimensions.unpriced() {
+                if tariff.prices(dimension) {
+                    warnings.push(Warning::UnpricedVolume {
+                        tariff_index,
+                        period_index: index,
+                        dimension,
+                    });
+                } else if !untariffed_volumes.contains(&(tariff_index, dimension)) {
+                    untariffed_volumes.push((tariff_index, dimension));
+                    warnings.push(Warning::UntariffedVolume {
+                        tariff_index,
+                        period_index: index,
+                        dimension,
+                    });
+                }
+            }
+
+            let overflow = |dimension| Error::Overflow {
+                tariff_index: Some(tariff_index),
+                period_index: index,
+                dimension,
+            };
+
+            add_duration(&mut total_charging_time, dimensions.time.volume)
+                .ok_or_else(|| overflow(DimensionType::Time))?;
 
             total_energy += dimensions.energy.volume.unwrap_or_else(Kwh::zero);
 
-            total_parking_time.0 = total_parking_time.0
-                + dimensions
-                    .parking_time
-                    .volume
-                    .map(|hms| hms.0)
-                    .unwrap_or_else(Duration::zero);
+            add_duration(&mut total_parking_time, dimensions.parking_time.volume)
+                .ok_or_else(|| overflow(DimensionType::ParkingTime))?;
 
-            periods.push(PeriodReport::new(period, dimensions));
+            add_duration(
+                &mut total_reservation_time,
+                dimensions.reservation_time.volume,
+            )
+            .ok_or_else(|| overflow(DimensionType::ReservationTime))?;
+
+            periods.push(PeriodReport::new(period, tariff_index, tariff, dimensions));
         }
 
-        let billed_charging_time = step_size.apply_time(&mut periods, total_charging_time);
-        let billed_energy = step_size.apply_energy(&mut periods, total_energy);
-        let billed_parking_time = step_size.apply_parking_time(&mut periods, total_parking_time);
+        apply_flat_fee(self.options.flat_fee, &mut periods, |dimensions| {
+            &mut dimensions.flat
+        });
+        apply_flat_fee(self.options.flat_fee, &mut periods, |dimensions| {
+            &mut dimensions.reservation_flat
+        });
+
+        let scope = self.options.step_size_scope;
+
+        let billed_charging_time = if step_size.skips_time(self.options.time_step_size) {
+            total_charging_time
+        } else {
+            step_size.apply(
+                scope,
+                &mut periods,
+                total_charging_time,
+                DimensionType::Time,
+                |dimensions| &mut dimensions.time,
+            )?
+        };
+
+        let billed_energy = step_size.apply(
+            scope,
+            &mut periods,
+            total_energy,
+            DimensionType::Energy,
+            |dimensions| &mut dimensions.energy,
+        )?;
+
+        let billed_parking_time = step_size.apply(
+            scope,
+            &mut periods,
+            total_parking_time,
+            DimensionType::ParkingTime,
+            |dimensions| &mut dimensions.parking_time,
+        )?;
+
+        let billed_reservation_time = step_size.apply(
+            scope,
+            &mut periods,
+            total_reservation_time,
+            DimensionType::ReservationTime,
+            |dimensions| &mut dimensions.reservation_time,
+        )?;
+
+        let rounding = self.rounding.for_currency(currency);
+
+        if rounding.scope == RoundingScope::Period {
+            for period in &mut periods {
+                period.dimensions.round_billed_volumes(&rounding);
+            }
+        }
 
         let mut total_energy_cost = Price::zero();
         let mut total_time_cost = Price::zero();
         let mut total_parking_cost = Price::zero();
         let mut total_fixed_cost = Price::zero();
+        let mut total_reservation_cost = Price::zero();
 
         for period in &periods {
             let dimensions = &period.dimensions;
 
-            total_energy_cost += dimensions.energy.cost();
-            total_time_cost += dimensions.time.cost();
-            total_parking_cost += dimensions.parking_time.cost();
-            total_fixed_cost += dimensions.flat.cost();
+            total_energy_cost += dimensions.energy.rounded_cost(&rounding);
+            total_time_cost += dimensions.time.rounded_cost(&rounding);
+            total_parking_cost += dimensions.parking_time.rounded_cost(&rounding);
+            total_fixed_cost += dimensions.flat.rounded_cost(&rounding);
+            total_reservation_cost += dimensions.reservation_time.rounded_cost(&rounding)
+                + dimensions.reservation_flat.rounded_cost(&rounding);
+        }
+
+        if rounding.scope != RoundingScope::Summary {
+            total_energy_cost = rounding.price(total_energy_cost);
+            total_time_cost = rounding.price(total_time_cost);
+            total_parking_cost = rounding.price(total_parking_cost);
+            total_fixed_cost = rounding.price(total_fixed_cost);
+            total_reservation_cost = rounding.price(total_reservation_cost);
         }
 
-        let total_time = if let Some(first) = periods.first() {
-            let last = periods.last().unwrap();
+        let total_time = if let (Some(first), Some(last)) = (periods.first(), periods.last()) {
             (last.end_date_time - first.start_date_time).into()
         } else {
             HoursDecimal::zero()
         };
 
-        let total_cost =
-            total_time_cost + total_parking_cost + total_fixed_cost + total_energy_cost;
+        let total_cost = total_time_cost
+            + total_parking_cost
+            + total_fixed_cost
+            + total_energy_cost
+            + total_reservation_cost;
 
-        let report = Report {
+        let price_adjustment = PriceAdjustment::new(session_tariff, session_rate, total_cost);
+
+        let total_cost = price_adjustment
+            .as_ref()
+            .map(|adjustment| adjustment.bound)
+            .unwrap_or(total_cost);
+
+        let tax_summary =
+            VatSummary::new(&periods, price_adjustment.as_ref(), total_cost, &rounding);
+
+        let mut report = Report {
             periods,
-            tariff_index,
             total_cost,
+            price_adjustment,
             total_time_cost,
             total_charging_time,
             total_time,
@@ -141,17 +596,215 @@ impl Pricer {
             billed_parking_time,
             billed_energy,
             billed_charging_time,
-            total_reservation_cost: Price::zero(),
+            total_reservation_cost,
+            total_reservation_time,
+            billed_reservation_time,
+            exchange_rates,
+            tariff_selection,
+            tax_summary,
+            rounding: self.rounding,
+            currency,
+            options: self.options,
+            profile: self.options.profile(),
+            trace: self.explain.then_some(trace),
+            warnings,
         };
 
+        if rounding.scope != RoundingScope::Summary {
+            report.round_volumes();
+        }
+
         Ok(report)
     }
 }
 
+/// Add the optional `volume` of a duration dimension to `total`. Returns `None` on overflow.
+fn add_duration(total: &mut HoursDecimal, volume: Option<HoursDecimal>) -> Option<()> {
+    if let Some(volume) = volume {
+        *total = total.0.checked_add(&volume.0)?.into();
+    }
+
+    Some(())
+}
+
+/// Only bill the flat dimension selected by `dimension` in the periods that should carry the flat
+/// fee according to `flat_fee`.
+fn apply_flat_fee<F>(flat_fee: FlatFee, periods: &mut [PeriodReport], mut dimension: F)
+where
+    F: FnMut(&mut Dimensions) -> &mut DimensionReport<()>,
+{
+    let mut previous = None;
+
+    for period in periods.iter_mut() {
+        let tariff_index = period.tariff_index;
+        let flat = dimension(&mut period.dimensions);
+
+        let element = flat
+            .price
+            .as_ref()
+            .map(|component| (tariff_index, component.tariff_element_index));
+
+        let is_charged = match flat_fee {
+            FlatFee::OncePerSession => element.is_some() && previous.is_none(),
+            FlatFee::OncePerElementActivation => element.is_some() && element != previous,
+        };
+
+        if !is_charged {
+            flat.billed_volume = None;
+        }
+
+        if flat_fee == FlatFee::OncePerElementActivation || previous.is_none() {
+            previous = element;
+        }
+    }
+}
+
+/// The price component that determines the step size of a dimension, together with the period
+/// in which it was last active.
+struct StepSizeComponent {
+    period_index: usize,
+    tariff_index: usize,
+    price: PriceComponent,
+}
+
+impl StepSizeComponent {
+    fn new(period_index: usize, tariff_index: usize, price: &PriceComponent) -> Self {
+        Self {
+            period_index,
+            tariff_index,
+            price: price.clone(),
+        }
+    }
+
+    fn missing_volume(&self, dimension: DimensionType) -> Error {
+        Error::MissingVolume {
+            tariff_index: self.tariff_index,
+            period_index: self.period_index,
+            dimension,
+        }
+    }
+
+    fn overflow(&self, dimension: DimensionType) -> Error {
+        Error::Overflow {
+            tariff_index: Some(self.tariff_index),
+            period_index: self.period_index,
+            dimension,
+        }
+    }
+
+    /// Bill the `volume` that is added by the step size of this component in the period in which
+    /// it was last active, in the dimension selected by `dimension_report`.
+    fn bill<V, F>(
+        &self,
+        periods: &mut [PeriodReport],
+        volume: V,
+        scope: StepSizeScope,
+        dimension: DimensionType,
+        dimension_report: F,
+    ) -> Result<()>
+    where
+        V: StepSizeVolume,
+        F: FnOnce(&mut Dimensions) -> &mut DimensionReport<V>,
+    {
+        let report = periods
+            .get_mut(self.period_index)
+            .map(|period| dimension_report(&mut period.dimensions))
+            .ok_or_else(|| self.missing_volume(dimension))?;
+
+        let billed_volume = report
+            .billed_volume
+            .ok_or_else(|| self.missing_volume(dimension))?;
+
+        report.billed_volume = Some(
+            billed_volume
+                .checked_add(volume)
+                .ok_or_else(|| self.overflow(dimension))?,
+        );
+
+        report.step_size = Some(StepSizeAdjustment {
+            volume,
+            step_size: self.price.step_size,
+            scope,
+            tariff_index: self.tariff_index,
+            tariff_element_index: self.price.tariff_element_index,
+        });
+
+        Ok(())
+    }
+}
+
+/// A volume that a step size can be applied to.
+trait StepSizeVolume: Copy {
+    /// Round the volume up to a multiple of `step_size`, in seconds or watt hours. Returns `None`
+    /// on overflow.
+    fn round_up(self, step_size: u64) -> Option<Self>;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+impl StepSizeVolume for HoursDecimal {
+    fn round_up(self, step_size: u64) -> Option<Self> {
+        if step_size == 0 {
+            return Some(self);
+        }
+
+        let total_seconds = Number::from(self.0.num_milliseconds()) / Number::from(1000_i64);
+        let step_size = Number::from(step_size);
+
+        let rounded_seconds = ((total_seconds / step_size).ceil() * step_size)
+            .try_into()
+            .ok()?;
+
+        Duration::try_seconds(rounded_seconds).map(Into::into)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(&other.0).map(Into::into)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(&other.0).map(Into::into)
+    }
+}
+
+impl StepSizeVolume for Kwh {
+    fn round_up(self, step_size: u64) -> Option<Self> {
+        if step_size == 0 {
+            return Some(self);
+        }
+
+        let step_size = Number::from(step_size);
+
+        Some(Kwh::from_watt_hours(
+            (self.watt_hours() / step_size).ceil() * step_size,
+        ))
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        Some(self - other)
+    }
+}
+
+/// The volume of a dimension that is priced by a single tariff element.
+struct ElementVolume<V> {
+    /// The last period that is priced by the element.
+    period_index: usize,
+    tariff_index: usize,
+    price: PriceComponent,
+    volume: V,
+}
+
 struct StepSize {
-    time: Option<(usize, PriceComponent)>,
-    parking_time: Option<(usize, PriceComponent)>,
-    energy: Option<(usize, PriceComponent)>,
+    time: Option<StepSizeComponent>,
+    parking_time: Option<StepSizeComponent>,
+    reservation_time: Option<StepSizeComponent>,
+    energy: Option<StepSizeComponent>,
 }
 
 impl StepSize {
@@ -159,102 +812,216 @@ impl StepSize {
         Self {
             time: None,
             parking_time: None,
+            reservation_time: None,
             energy: None,
         }
     }
 
-    fn update(&mut self, index: usize, components: &PriceComponents, period: &ChargePeriod) {
+    fn update(
+        &mut self,
+        index: usize,
+        tariff_index: usize,
+        components: &PriceComponents,
+        period: &ChargePeriod,
+    ) {
         if period.period_data.energy.is_some() {
             if let Some(energy) = &components.energy {
-                self.energy = Some((index, energy.clone()));
+                self.energy = Some(StepSizeComponent::new(index, tariff_index, energy));
             }
         }
 
         if period.period_data.duration.is_some() {
             if let Some(time) = &components.time {
-                self.time = Some((index, time.clone()));
+                self.time = Some(StepSizeComponent::new(index, tariff_index, time));
             }
         }
 
         if period.period_data.parking_duration.is_some() {
             if let Some(parking) = &components.parking {
-                self.parking_time = Some((index, parking.clone()));
+                self.parking_time = Some(StepSizeComponent::new(index, tariff_index, parking));
             }
         }
-    }
 
-    fn duration_step_size(
-        total: HoursDecimal,
-        billed_volume: &mut HoursDecimal,
-        step_size: u64,
-    ) -> HoursDecimal {
-        let total_seconds = Number::from(total.0.num_seconds());
-        let step_size = Number::from(step_size);
+        if period.period_data.reservation_duration.is_some() {
+            if let Some(reservation) = &components.reservation_time {
+                self.reservation_time =
+                    Some(StepSizeComponent::new(index, tariff_index, reservation));
+            }
+        }
+    }
 
-        let priced_total_seconds = ((total_seconds / step_size).ceil() * step_size)
-            .try_into()
-            .expect("overflow");
+    /// Whether the step size of the `TIME` component is skipped. Unless `time_step_size` is
+    /// [`TimeStepSize::Always`], it's skipped when the session has parking time that is priced.
+    fn skips_time(&self, time_step_size: TimeStepSize) -> bool {
+        time_step_size == TimeStepSize::UnlessParking && self.parking_time.is_some()
+    }
 
-        let priced_total = Duration::seconds(priced_total_seconds);
-        let difference = priced_total - total.0;
-        billed_volume.0 = billed_volume.0 + difference;
+    /// Apply the step size to the `total` volume of `dimension`, where the volume that the step
+    /// size applies to is selected by `scope`. Returns the billed volume of the session.
+    fn apply<V, F>(
+        &self,
+        scope: StepSizeScope,
+        periods: &mut [PeriodReport],
+        total: V,
+        dimension: DimensionType,
+        dimension_report: F,
+    ) -> Result<V>
+    where
+        V: StepSizeVolume,
+        F: FnMut(&mut Dimensions) -> &mut DimensionReport<V>,
+    {
+        match scope {
+            StepSizeScope::Session => {
+                let component = match dimension {
+                    DimensionType::Energy => self.energy.as_ref(),
+                    DimensionType::Time => self.time.as_ref(),
+                    DimensionType::ParkingTime => self.parking_time.as_ref(),
+                    DimensionType::ReservationTime => self.reservation_time.as_ref(),
+                    DimensionType::Flat | DimensionType::ReservationFlat => None,
+                };
 
-        priced_total.into()
+                Self::apply_session(component, periods, total, dimension, dimension_report)
+            }
+            StepSizeScope::Element | StepSizeScope::LastElement => {
+                Self::apply_per_element(scope, periods, total, dimension, dimension_report)
+            }
+        }
     }
 
-    fn apply_time(&self, periods: &mut [PeriodReport], total: HoursDecimal) -> HoursDecimal {
-        if let (Some((time_index, price)), None) = (&self.time, &self.parking_time) {
-            let period = &mut periods[*time_index];
-            let volume = &mut period
-                .dimensions
-                .time
-                .billed_volume
-                .expect("dimension should have a volume");
+    /// Apply the step size of `component` to the total volume of the session, the additional
+    /// volume is billed in the period in which the component was last active.
+    fn apply_session<V, F>(
+        component: Option<&StepSizeComponent>,
+        periods: &mut [PeriodReport],
+        total: V,
+        dimension: DimensionType,
+        dimension_report: F,
+    ) -> Result<V>
+    where
+        V: StepSizeVolume,
+        F: FnOnce(&mut Dimensions) -> &mut DimensionReport<V>,
+    {
+        let Some(component) = component else {
+            return Ok(total);
+        };
 
-            Self::duration_step_size(total, volume, price.step_size)
-        } else {
-            total
+        if component.price.step_size == 0 {
+            return Ok(total);
         }
+
+        let billed = total
+            .round_up(component.price.step_size)
+            .ok_or_else(|| component.overflow(dimension))?;
+
+        let difference = billed
+            .checked_sub(total)
+            .ok_or_else(|| component.overflow(dimension))?;
+
+        component.bill(
+            periods,
+            difference,
+            StepSizeScope::Session,
+            dimension,
+            dimension_report,
+        )?;
+
+        Ok(billed)
     }
 
-    fn apply_parking_time(
-        &self,
+    /// Collect the volume that is priced by each tariff element in the dimension selected by
+    /// `dimension_report`, together with the index of the last period priced by the element.
+    fn element_volumes<V, F>(
         periods: &mut [PeriodReport],
-        total: HoursDecimal,
-    ) -> HoursDecimal {
-        if let Some((parking_index, price)) = &self.parking_time {
-            let period = &mut periods[*parking_index];
-            let volume = period
-                .dimensions
-                .parking_time
-                .billed_volume
-                .as_mut()
-                .expect("dimension should have a volume");
-
-            Self::duration_step_size(total, volume, price.step_size)
-        } else {
-            total
+        mut dimension_report: F,
+        dimension: DimensionType,
+    ) -> Result<Vec<ElementVolume<V>>>
+    where
+        V: StepSizeVolume,
+        F: FnMut(&mut Dimensions) -> &mut DimensionReport<V>,
+    {
+        let mut elements: Vec<ElementVolume<V>> = Vec::new();
+
+        for (period_index, period) in periods.iter_mut().enumerate() {
+            let tariff_index = period.tariff_index;
+            let report = dimension_report(&mut period.dimensions);
+
+            let (Some(component), Some(volume)) = (&report.price, report.billed_volume) else {
+                continue;
+            };
+
+            let element = elements.iter_mut().find(|element| {
+                element.tariff_index == tariff_index
+                    && element.price.tariff_element_index == component.tariff_element_index
+            });
+
+            if let Some(element) = element {
+                element.volume = element.volume.checked_add(volume).ok_or(Error::Overflow {
+                    tariff_index: Some(tariff_index),
+                    period_index,
+                    dimension,
+                })?;
+                element.period_index = period_index;
+            } else {
+                elements.push(ElementVolume {
+                    period_index,
+                    tariff_index,
+                    price: component.clone(),
+                    volume,
+                });
+            }
         }
+
+        Ok(elements)
     }
 
-    fn apply_energy(&self, periods: &mut [PeriodReport], total: Kwh) -> Kwh {
-        if let Some((energy_index, price)) = &self.energy {
-            let period = &mut periods[*energy_index];
-            let volume = &mut period
-                .dimensions
-                .energy
-                .billed_volume
-                .expect("dimension should have a volume");
+    /// Apply the step size of each tariff element to the volume that it priced, the additional
+    /// volume is billed in the last period priced by the element. With
+    /// [`StepSizeScope::LastElement`] only the element that was active last is rounded up.
+    fn apply_per_element<V, F>(
+        scope: StepSizeScope,
+        periods: &mut [PeriodReport],
+        total: V,
+        dimension: DimensionType,
+        mut dimension_report: F,
+    ) -> Result<V>
+    where
+        V: StepSizeVolume,
+        F: FnMut(&mut Dimensions) -> &mut DimensionReport<V>,
+    {
+        let mut elements = Self::element_volumes(periods, &mut dimension_report, dimension)?;
 
-            let step_size = Number::from(price.step_size);
+        if scope == StepSizeScope::LastElement {
+            let last = elements.iter().map(|element| element.period_index).max();
+            elements.retain(|element| Some(element.period_index) == last);
+        }
 
-            let billed = Kwh::from_watt_hours((total.watt_hours() / step_size).ceil() * step_size);
-            *volume += total - billed;
+        let mut billed = total;
 
-            billed
-        } else {
-            total
+        for element in elements {
+            let component = StepSizeComponent {
+                period_index: element.period_index,
+                tariff_index: element.tariff_index,
+                price: element.price,
+            };
+
+            if component.price.step_size == 0 {
+                continue;
+            }
+
+            let difference = element
+                .volume
+                .round_up(component.price.step_size)
+                .and_then(|element_billed| element_billed.checked_sub(element.volume))
+                .ok_or_else(|| component.overflow(dimension))?;
+
+            component.bill(periods, difference, scope, dimension, &mut dimension_report)?;
+
+            billed = billed
+                .checked_add(difference)
+                .ok_or_else(|| component.overflow(dimension))?;
         }
+
+        Ok(billed)
     }
 }
 
@@ -264,10 +1031,14 @@ impl StepSize {
 pub struct Report {
     /// Charge session details per period.
     pub periods: Vec<PeriodReport>,
-    /// Index of the tariff that was found to be active.
-    pub tariff_index: usize,
     /// Total sum of all the costs of this transaction in the specified currency.
+    ///
+    /// When the tariff specifies a `min_price` or `max_price` this total is limited to these
+    /// bounds, see `price_adjustment`.
     pub total_cost: Price,
+    /// The adjustment that was made to the sum of all the periods to arrive at `total_cost`, when
+    /// this sum was outside of the `min_price` or `max_price` of the tariff.
+    pub price_adjustment: Option<PriceAdjustment>,
     /// Total sum of all the cost related to duration of charging during this transaction, in the specified currency.
     pub total_time_cost: Price,
     /// Total duration of the charging session (including the duration of charging and not charging), in hours.
@@ -292,6 +1063,217 @@ pub struct Report {
     pub total_fixed_cost: Price,
     /// Total sum of all the cost related to a reservation of a Charge Point, including fixed price components, in the specified currency.
     pub total_reservation_cost: Price,
+    /// Total duration of the reservation that preceded the charging session, in hours.
+    pub total_reservation_time: HoursDecimal,
+    /// The total reservation time after applying step-size.
+    pub billed_reservation_time: HoursDecimal,
+    /// The exchange rates that were used to convert the prices of tariffs into the currency of
+    /// the CDR. Empty when all tariffs use the currency of the CDR.
+    pub exchange_rates: Vec<ExchangeRate>,
+    /// Explains for each tariff why the [`TariffSelector`] of the pricer did or didn't select it
+    /// at the start of the session. A charging period that references a tariff by its
+    /// `tariff_id` uses that tariff instead.
+    pub tariff_selection: Vec<TariffCandidate>,
+    /// The costs of the session grouped by VAT rate, in the order in which the rates are first
+    /// priced. The amounts of each rate are always rounded according to the `rounding` policy,
+    /// such that they can be used to invoice the session.
+    pub tax_summary: Vec<VatSummary>,
+    /// The rounding policy that was applied to this report. Without a number of decimals for
+    /// monetary amounts, these are rounded to the minor units of `currency`.
+    pub rounding: Rounding,
+    /// The currency of the CDR, in which all costs are expressed.
+    pub currency: Currency,
+    /// The reading of the ambiguous parts of the OCPI specification that was used.
+    pub options: PricerOptions,
+    /// The named profile of `options`.
+    pub profile: Profile,
+    /// How the tariff elements were evaluated for each period, in the same order as `periods`.
+    /// Only recorded when requested with [`Pricer::explain`].
+    pub trace: Option<Vec<PeriodTrace>>,
+    /// The input that is suspicious, but that could still be priced. In the order in which it
+    /// was found.
+    pub warnings: Vec<Warning>,
+}
+
+impl Report {
+    /// Round the total and billed volumes of the session.
+    fn round_volumes(&mut self) {
+        let rounding = self.rounding;
+
+        self.total_time = rounding.duration(self.total_time);
+        self.total_charging_time = rounding.duration(self.total_charging_time);
+        self.billed_charging_time = rounding.duration(self.billed_charging_time);
+        self.total_parking_time = rounding.duration(self.total_parking_time);
+        self.billed_parking_time = rounding.duration(self.billed_parking_time);
+        self.total_energy = rounding.energy(self.total_energy);
+        self.billed_energy = rounding.energy(self.billed_energy);
+        self.total_reservation_time = rounding.duration(self.total_reservation_time);
+        self.billed_reservation_time = rounding.duration(self.billed_reservation_time);
+    }
+}
+
+/// The costs of a session that are priced using a single VAT rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VatSummary {
+    /// The VAT percentage, `None` for the costs of price components without a VAT.
+    pub vat: Option<Vat>,
+    /// The rounded sum of the costs excluding VAT. A `price_adjustment` of the session is
+    /// distributed over the rates in proportion to their costs.
+    pub taxable_amount: Money,
+    /// The VAT over the taxable amount, the difference between the gross and taxable amounts.
+    pub tax_amount: Money,
+    /// The rounded amount including VAT. Depending on the VAT rounding of the policy, it's
+    /// calculated from the rounded or the unrounded taxable amount.
+    pub gross_amount: Money,
+}
+
+impl VatSummary {
+    /// Group the costs of `periods` by their VAT rate. The sums are rounded using `rounding`,
+    /// unless it rounds each period the costs are summed unrounded.
+    ///
+    /// The rounded amounts add up to the rounded `total_cost`, a difference caused by rounding the
+    /// rates separately is assigned to the rate with the largest gross amount.
+    fn new(
+        periods: &[PeriodReport],
+        adjustment: Option<&PriceAdjustment>,
+        total_cost: Price,
+        rounding: &Rounding,
+    ) -> Vec<Self> {
+        let mut rates: Vec<(Option<Vat>, Money)> = Vec::new();
+
+        for period in periods {
+            for (vat, cost) in period.dimensions.costs_by_vat() {
+                let cost = if rounding.scope == RoundingScope::Period {
+                    rounding.money(cost)
+                } else {
+                    cost
+                };
+
+                if let Some((_, total)) = rates.iter_mut().find(|(rate, _)| *rate == vat) {
+                    *total = *total + cost;
+                } else {
+                    rates.push((vat, cost));
+                }
+            }
+        }
+
+        let total = rates
+            .iter()
+            .fold(Money::zero(), |total, &(_, cost)| total + cost);
+
+        if let Some(adjustment) = adjustment.filter(|_| total != Money::zero()) {
+            for (_, cost) in &mut rates {
+                let share = Number::from(*cost) / Number::from(total);
+                *cost = *cost + adjustment.amount.excl_vat * share;
+            }
+        }
+
+        let mut summary: Vec<Self> = rates
+            .into_iter()
+            .map(|(vat, cost)| {
+                let price = rounding.price_with_vat(cost, vat);
+
+                Self {
+                    vat,
+                    taxable_amount: price.excl_vat,
+                    tax_amount: price.incl_vat - price.excl_vat,
+                    gross_amount: price.incl_vat,
+                }
+            })
+            .collect();
+
+        // Without costs to distribute it over, the adjustment is taxed at the VAT of the limit.
+        if let Some(adjustment) = adjustment.filter(|_| total == Money::zero()) {
+            let Price { excl_vat, incl_vat } = adjustment.amount;
+
+            if let Some(rate) = summary.iter_mut().find(|rate| rate.vat == adjustment.vat) {
+                rate.taxable_amount = rate.taxable_amount + excl_vat;
+                rate.gross_amount = rate.gross_amount + incl_vat;
+                rate.tax_amount = rate.gross_amount - rate.taxable_amount;
+            } else {
+                summary.push(Self {
+                    vat: adjustment.vat,
+                    taxable_amount: excl_vat,
+                    tax_amount: incl_vat - excl_vat,
+                    gross_amount: incl_vat,
+                });
+            }
+        }
+
+        let total_cost = rounding.price(total_cost);
+
+        let (taxable_amount, gross_amount) = summary
+            .iter()
+            .fold((Money::zero(), Money::zero()), |(taxable, gross), rate| {
+                (taxable + rate.taxable_amount, gross + rate.gross_amount)
+            });
+
+        if let Some(largest) = summary.iter_mut().max_by_key(|rate| rate.gross_amount) {
+            largest.taxable_amount =
+                largest.taxable_amount + (total_cost.excl_vat - taxable_amount);
+            largest.gross_amount = largest.gross_amount + (total_cost.incl_vat - gross_amount);
+        }
+
+        for rate in &mut summary {
+            rate.taxable_amount = rounding.money(rate.taxable_amount);
+            rate.gross_amount = rounding.money(rate.gross_amount);
+            rate.tax_amount = rate.gross_amount - rate.taxable_amount;
+        }
+
+        summary
+    }
+}
+
+/// Describes how the total cost of a session was limited by the `min_price` or `max_price` of
+/// the tariff.
+#[derive(Serialize)]
+pub struct PriceAdjustment {
+    /// The tariff field that caused the adjustment.
+    pub limit: PriceLimit,
+    /// The value of the `min_price` or `max_price` of the tariff.
+    pub bound: Price,
+    /// The amount that was added to the sum of all periods to arrive at the total cost. This
+    /// amount is negative when the `max_price` was applied.
+    pub amount: Price,
+    /// The VAT of the `min_price` or `max_price`, the VAT of the price components of the tariff
+    /// when they all have the same VAT.
+    pub vat: Option<Vat>,
+}
+
+impl PriceAdjustment {
+    /// Check the sum of all periods against the price limits of `tariff`, converted using `rate`
+    /// when provided. The excluding VAT amount is leading, when it's out of bounds the total
+    /// becomes exactly the bound.
+    fn new(tariff: &Tariff, rate: Option<Number>, total_cost: Price) -> Option<Self> {
+        let convert = |price: Price| rate.map(|rate| price * rate).unwrap_or(price);
+
+        let (limit, bound) = match (tariff.min_price.map(convert), tariff.max_price.map(convert)) {
+            (Some(min_price), _) if total_cost.excl_vat < min_price.excl_vat => {
+                (PriceLimit::MinPrice, min_price)
+            }
+            (_, Some(max_price)) if total_cost.excl_vat > max_price.excl_vat => {
+                (PriceLimit::MaxPrice, max_price)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            limit,
+            bound,
+            amount: bound - total_cost,
+            vat: tariff.price_limit_vat,
+        })
+    }
+}
+
+/// The tariff field that limits the total cost of a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PriceLimit {
+    /// The session would have been cheaper than the `min_price` of the tariff.
+    MinPrice,
+    /// The session would have been more expensive than the `max_price` of the tariff.
+    MaxPrice,
 }
 
 /// A report for a single period that occurred during a session.
@@ -301,15 +1283,42 @@ pub struct PeriodReport {
     pub start_date_time: DateTime<Utc>,
     /// The end time of this period.
     pub end_date_time: DateTime<Utc>,
+    /// The index of the charging period in the CDR this period originates from.
+    pub cdr_period_index: usize,
+    /// Is set when a charging period of the CDR was split into multiple periods and this period
+    /// starts at such a split. The volumes of a split period are divided over the parts in
+    /// proportion to their duration.
+    pub split: Option<PeriodSplit>,
+    /// Whether the volumes of this period are interpolated from a charging period of the CDR
+    /// that was split, assuming a constant rate of consumption during that charging period.
+    pub is_interpolated: bool,
+    /// Index of the tariff that was used to price this period.
+    pub tariff_index: usize,
+    /// The id of the tariff that was used to price this period. This is the `tariff_id` of the
+    /// charging period in the CDR when it references one of the tariffs.
+    pub tariff_id: String,
+    /// The values of the dimensions of the CDR during this period.
+    pub cdr_dimensions: CdrDimensions,
     /// A structure that contains results per dimension.
     pub dimensions: Dimensions,
 }
 
 impl PeriodReport {
-    fn new(period: &ChargePeriod, dimensions: Dimensions) -> Self {
+    fn new(
+        period: &ChargePeriod,
+        tariff_index: usize,
+        tariff: &Tariff,
+        dimensions: Dimensions,
+    ) -> Self {
         Self {
             start_date_time: period.start_instant.date_time,
             end_date_time: period.end_instant.date_time,
+            cdr_period_index: period.cdr_period_index,
+            split: period.split,
+            is_interpolated: period.is_interpolated,
+            tariff_index,
+            tariff_id: tariff.id.clone(),
+            cdr_dimensions: CdrDimensions::new(&period.period_data),
             dimensions,
         }
     }
@@ -320,13 +1329,66 @@ impl PeriodReport {
             + self.dimensions.parking_time.cost()
             + self.dimensions.flat.cost()
             + self.dimensions.energy.cost()
+            + self.dimensions.reservation_time.cost()
+            + self.dimensions.reservation_flat.cost()
+    }
+}
+
+/// The values of the dimensions of the CDR during a period. The volumes of a period that is split
+/// are interpolated.
+#[derive(Serialize)]
+pub struct CdrDimensions {
+    /// The average charging current.
+    pub current: Option<Ampere>,
+    /// The peak current.
+    pub max_current: Option<Ampere>,
+    /// The lowest current.
+    pub min_current: Option<Ampere>,
+    /// The maximum power.
+    pub max_power: Option<Kw>,
+    /// The minimum power.
+    pub min_power: Option<Kw>,
+    /// The energy that is priced. This is the `ENERGY` volume, or the `ENERGY_IMPORT` volume
+    /// when the period has no `ENERGY` dimension.
+    pub energy: Option<Kwh>,
+    /// The energy exported from the EV to the grid.
+    pub energy_export: Option<Kwh>,
+    /// The energy imported from the grid to the EV.
+    pub energy_import: Option<Kwh>,
+    /// The state of charge of the battery of the EV at the end of the period.
+    pub state_of_charge: Option<Percentage>,
+    /// The charging time.
+    pub time: Option<HoursDecimal>,
+    /// The parking time.
+    pub parking_time: Option<HoursDecimal>,
+    /// The reservation time.
+    pub reservation_time: Option<HoursDecimal>,
+}
+
+impl CdrDimensions {
+    fn new(data: &PeriodData) -> Self {
+        Self {
+            current: data.current,
+            max_current: data.max_current,
+            min_current: data.min_current,
+            max_power: data.max_power,
+            min_power: data.min_power,
+            energy: data.energy,
+            energy_export: data.energy_export,
+            energy_import: data.energy_import,
+            state_of_charge: data.state_of_charge,
+            time: data.duration.map(Into::into),
+            parking_time: data.parking_duration.map(Into::into),
+            reservation_time: data.reservation_duration.map(Into::into),
+        }
     }
 }
 
 /// A structure containing a report for each dimension.
 #[derive(Serialize)]
 pub struct Dimensions {
-    /// The flat dimension.
+    /// The flat dimension. The `billed_volume` of this dimension is only set in the periods that
+    /// carry the flat fee, see [`FlatFee`].
     pub flat: DimensionReport<()>,
     /// The energy dimension.
     pub energy: DimensionReport<Kwh>,
@@ -334,11 +1396,87 @@ pub struct Dimensions {
     pub time: DimensionReport<HoursDecimal>,
     /// The parking time dimension.
     pub parking_time: DimensionReport<HoursDecimal>,
+    /// The reservation time dimension, priced by the time component of a tariff element with a
+    /// reservation restriction.
+    pub reservation_time: DimensionReport<HoursDecimal>,
+    /// The flat dimension of a tariff element with a reservation restriction. Like the `flat`
+    /// dimension, it's only billed in the periods that carry the flat fee.
+    pub reservation_flat: DimensionReport<()>,
 }
 
 impl Dimensions {
+    /// The cost excluding VAT of each priced dimension, together with the VAT of its price
+    /// component.
+    fn costs_by_vat(&self) -> impl Iterator<Item = (Option<Vat>, Money)> {
+        fn cost<V>(dimension: &DimensionReport<V>) -> Option<(Option<Vat>, Money)>
+        where
+            V: Mul<Money, Output = Money> + Copy,
+        {
+            let component = dimension.price.as_ref()?;
+            Some((component.vat, dimension.cost_excl_vat()))
+        }
+
+        [
+            cost(&self.flat),
+            cost(&self.energy),
+            cost(&self.time),
+            cost(&self.parking_time),
+            cost(&self.reservation_time),
+            cost(&self.reservation_flat),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The dimensions that have a volume, but no price component.
+    fn unpriced(&self) -> impl Iterator<Item = DimensionType> {
+        fn is_unpriced<V>(dimension: &DimensionReport<V>) -> bool
+        where
+            V: PartialEq + Default,
+        {
+            let has_volume = dimension
+                .volume
+                .as_ref()
+                .map(|volume| *volume != V::default())
+                .unwrap_or(false);
+
+            dimension.price.is_none() && has_volume
+        }
+
+        [
+            (is_unpriced(&self.energy), DimensionType::Energy),
+            (is_unpriced(&self.time), DimensionType::Time),
+            (is_unpriced(&self.parking_time), DimensionType::ParkingTime),
+            (
+                is_unpriced(&self.reservation_time),
+                DimensionType::ReservationTime,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(is_unpriced, dimension)| is_unpriced.then_some(dimension))
+    }
+
+    /// Round the billed volume of each dimension.
+    fn round_billed_volumes(&mut self, rounding: &Rounding) {
+        self.energy.billed_volume = self.energy.billed_volume.map(|v| rounding.energy(v));
+        self.time.billed_volume = self.time.billed_volume.map(|v| rounding.duration(v));
+        self.parking_time.billed_volume = self
+            .parking_time
+            .billed_volume
+            .map(|v| rounding.duration(v));
+        self.reservation_time.billed_volume = self
+            .reservation_time
+            .billed_volume
+            .map(|v| rounding.duration(v));
+    }
+
     pub(crate) fn new(components: PriceComponents, data: &PeriodData) -> Self {
         Self {
+            reservation_time: DimensionReport::new(
+                components.reservation_time,
+                data.reservation_duration.map(Into::into),
+            ),
+            reservation_flat: DimensionReport::new(components.reservation_flat, Some(())),
             parking_time: DimensionReport::new(
                 components.parking,
                 data.parking_duration.map(Into::into),
@@ -367,8 +1505,26 @@ pub struct DimensionReport<V> {
     /// period.
     ///
     /// If no step-size was applied for this period, the volume is exactly equal to the `volume`
-    /// field.
+    /// field. Otherwise the additional volume is given by `step_size`.
     pub billed_volume: Option<V>,
+    /// The step size that was applied in this period, if any.
+    pub step_size: Option<StepSizeAdjustment<V>>,
+}
+
+/// The volume that was added to the `billed_volume` of a period by the step size of a price
+/// component.
+#[derive(Clone, Copy, Serialize)]
+pub struct StepSizeAdjustment<V> {
+    /// The additional volume that is billed in this period.
+    pub volume: V,
+    /// The step size of the price component, in seconds or watt hours.
+    pub step_size: u64,
+    /// The volume that the step size was applied to.
+    pub scope: StepSizeScope,
+    /// The index of the tariff that contains the price component.
+    pub tariff_index: usize,
+    /// The index of the tariff element that contains the price component.
+    pub tariff_element_index: usize,
 }
 
 impl<V> DimensionReport<V>
@@ -380,6 +1536,7 @@ where
             price: price_component,
             volume,
             billed_volume: volume,
+            step_size: None,
         }
     }
 }
@@ -410,6 +1567,16 @@ where
         }
     }
 
+    /// The cost of this dimension during a period, rounded when `rounding` rounds each period.
+    fn rounded_cost(&self, rounding: &Rounding) -> Price {
+        if rounding.scope == RoundingScope::Period {
+            let vat = self.price.as_ref().and_then(|component| component.vat);
+            rounding.price_with_vat(self.cost_excl_vat(), vat)
+        } else {
+            self.cost()
+        }
+    }
+
     /// The cost including VAT of this dimension during a period.
     pub fn cost_incl_vat(&self) -> Money {
         if let Some(vat) = self.price.as_ref().and_then(|c| c.vat) {
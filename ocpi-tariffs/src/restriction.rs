@@ -1,10 +1,14 @@
 use std::collections::HashSet;
 
-use chrono::{Duration, NaiveDate, NaiveTime, Timelike, Weekday};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
 
-use crate::ocpi::tariff::OcpiTariffRestriction;
-use crate::session::{InstantData, PeriodData};
+use crate::explain::RestrictionTrace;
+use crate::ocpi::tariff::{OcpiTariffRestriction, ReservationRestrictionType};
+use crate::options::RestrictionBound;
+use crate::session::{ChargePeriod, InstantData, PeriodData, Reservation, SplitPoint};
 use crate::types::electricity::{Ampere, Kw, Kwh};
+use crate::types::period::PeriodSplit;
+use crate::types::time::HoursDecimal;
 
 pub fn collect_restrictions(restriction: &OcpiTariffRestriction) -> Vec<Restriction> {
     let mut collected = Vec::new();
@@ -78,6 +82,10 @@ pub fn collect_restrictions(restriction: &OcpiTariffRestriction) -> Vec<Restrict
         ));
     }
 
+    if let Some(reservation) = &restriction.reservation {
+        collected.push(Restriction::Reservation(reservation.clone()));
+    }
+
     collected
 }
 
@@ -100,11 +108,29 @@ pub enum Restriction {
     MinDuration(Duration),
     MaxDuration(Duration),
     DayOfWeek(HashSet<Weekday>),
-    #[allow(dead_code)]
-    Reservation,
+    Reservation(ReservationRestrictionType),
 }
 
 impl Restriction {
+    /// Checks if this restriction is valid at the start of a period at `instant`, where `bound`
+    /// decides whether the `end_time`, `max_kwh` and `max_duration` are still valid.
+    pub fn instant_validity(&self, instant: &InstantData, bound: RestrictionBound) -> bool {
+        if bound == RestrictionBound::Exclusive {
+            return self.instant_validity_exclusive(instant);
+        }
+
+        match *self {
+            Self::WrappingTime {
+                start_time,
+                end_time,
+            } => instant.local_time() >= start_time || instant.local_time() <= end_time,
+            Self::EndTime(end_time) => instant.local_time() <= end_time,
+            Self::MaxKwh(max_energy) => instant.total_energy <= max_energy,
+            Self::MaxDuration(max_duration) => instant.total_duration <= max_duration,
+            _ => self.instant_validity_exclusive(instant),
+        }
+    }
+
     /// Checks if this restriction is valid at `instant`. The time based restrictions are
     /// treated as exclusive comparisons.
     pub fn instant_validity_exclusive(&self, instant: &InstantData) -> bool {
@@ -126,52 +152,192 @@ impl Restriction {
         }
     }
 
-    /// Checks if this restriction is valid at `instant`. The time based restriction are treated as
-    /// inclusive comparisons.
+    /// Find the first point after the start of `period` at which the validity of this restriction
+    /// changes. Either because of the passing of local time or because a threshold of the total
+    /// energy or charging duration of the session is reached.
     ///
-    /// For example an instant at 00:00 on a tuesday is regarded as valid for a restriction that
-    /// has a `DayOfWeek` which includes monday.
-    pub fn instant_validity_inclusive(&self, instant: &InstantData) -> bool {
-        match self {
+    /// The returned point could lie beyond the end of `period`.
+    pub fn next_boundary(&self, period: &ChargePeriod) -> Option<(SplitPoint, PeriodSplit)> {
+        let instant = &period.start_instant;
+
+        let (date_time, reason) = match self {
+            &Self::StartTime(time) | &Self::EndTime(time) => {
+                (instant.next_local_time(time)?, PeriodSplit::TimeOfDay)
+            }
             &Self::WrappingTime {
                 start_time,
                 end_time,
-            } => instant.local_time() >= start_time || instant.local_time() < end_time,
-            &Self::EndTime(end_time) => instant.local_time() <= end_time,
-            &Self::EndDate(end_date) => {
-                // Since the end date of this period is derived from the start date of the next period
-                // we can't do a exclusive comparison. But we should still check that this period doesn't
-                // end in the middle of the day on `end_date`.
+            } => (
+                [start_time, end_time]
+                    .into_iter()
+                    .filter_map(|time| instant.next_local_time(time))
+                    .min()?,
+                PeriodSplit::TimeOfDay,
+            ),
+            &Self::StartDate(date) | &Self::EndDate(date) => (
+                instant
+                    .local_midnight(date)
+                    .filter(|&date_time| date_time > instant.date_time)?,
+                PeriodSplit::Day,
+            ),
+            Self::DayOfWeek(days) => {
+                let today = instant.local_date();
+                let is_valid_today = days.contains(&today.weekday());
 
-                let is_before_end_date = instant.local_date() < end_date;
-                let is_on_end_date = instant.local_date() == end_date;
-                let is_at_midnight = instant.local_time().num_seconds_from_midnight() == 0;
+                // Find the first day that differs in validity from today.
+                let date_time = today
+                    .iter_days()
+                    .skip(1)
+                    .take(7)
+                    .find(|day| days.contains(&day.weekday()) != is_valid_today)
+                    .and_then(|day| instant.local_midnight(day))?;
 
-                is_before_end_date || (is_on_end_date && is_at_midnight)
+                (date_time, PeriodSplit::Day)
             }
-            &Self::MinKwh(min_energy) => instant.total_energy >= min_energy,
-            &Self::MaxKwh(max_energy) => instant.total_energy < max_energy,
-            &Self::MinDuration(min_duration) => instant.total_duration >= min_duration,
-            &Self::MaxDuration(max_duration) => instant.total_duration < max_duration,
+            &Self::MinKwh(energy) | &Self::MaxKwh(energy) => {
+                return Some((SplitPoint::Energy(energy), PeriodSplit::Energy))
+            }
+            &Self::MinDuration(duration) | &Self::MaxDuration(duration) => {
+                return Some((SplitPoint::Duration(duration), PeriodSplit::Duration))
+            }
+            _ => return None,
+        };
+
+        Some((SplitPoint::Instant(date_time), reason))
+    }
+
+    /// Explain whether this restriction is valid for a period that starts at `instant` with
+    /// `state`, together with the values that are compared.
+    pub fn explain(
+        &self,
+        instant: &InstantData,
+        state: &PeriodData,
+        restriction_bound: RestrictionBound,
+    ) -> RestrictionTrace {
+        let duration = |duration: Duration| HoursDecimal::from(duration).to_string();
+
+        let (restriction, bound, value) = match self {
+            Self::StartTime(time) => (
+                "start_time",
+                time.to_string(),
+                Some(instant.local_time().to_string()),
+            ),
+            Self::EndTime(time) => (
+                "end_time",
+                time.to_string(),
+                Some(instant.local_time().to_string()),
+            ),
+            Self::WrappingTime {
+                start_time,
+                end_time,
+            } => (
+                "start_time, end_time",
+                format!("{} - {}", start_time, end_time),
+                Some(instant.local_time().to_string()),
+            ),
+            Self::StartDate(date) => (
+                "start_date",
+                date.to_string(),
+                Some(instant.local_date().to_string()),
+            ),
+            Self::EndDate(date) => (
+                "end_date",
+                date.to_string(),
+                Some(instant.local_date().to_string()),
+            ),
+            Self::MinKwh(energy) => (
+                "min_kwh",
+                energy.to_string(),
+                Some(instant.total_energy.to_string()),
+            ),
+            Self::MaxKwh(energy) => (
+                "max_kwh",
+                energy.to_string(),
+                Some(instant.total_energy.to_string()),
+            ),
+            Self::MinCurrent(current) => (
+                "min_current",
+                current.to_string(),
+                state
+                    .min_current
+                    .or(state.current)
+                    .map(|current| current.to_string()),
+            ),
+            Self::MaxCurrent(current) => (
+                "max_current",
+                current.to_string(),
+                state
+                    .max_current
+                    .or(state.current)
+                    .map(|current| current.to_string()),
+            ),
+            Self::MinPower(power) => (
+                "min_power",
+                power.to_string(),
+                state.min_power.map(|power| power.to_string()),
+            ),
+            Self::MaxPower(power) => (
+                "max_power",
+                power.to_string(),
+                state.max_power.map(|power| power.to_string()),
+            ),
+            &Self::MinDuration(min_duration) => (
+                "min_duration",
+                duration(min_duration),
+                Some(duration(instant.total_duration)),
+            ),
+            &Self::MaxDuration(max_duration) => (
+                "max_duration",
+                duration(max_duration),
+                Some(duration(instant.total_duration)),
+            ),
             Self::DayOfWeek(days) => {
-                let includes_weekday = days.contains(&instant.local_weekday());
-                let includes_day_before = days.contains(&instant.local_weekday().pred());
-                let is_at_midnight = instant.local_time().num_seconds_from_midnight() == 0;
-                includes_weekday || (includes_day_before && is_at_midnight)
+                let mut days: Vec<_> = days.iter().collect();
+                days.sort_by_key(|day| day.num_days_from_monday());
+
+                let days: Vec<_> = days.iter().map(|day| format!("{:?}", day)).collect();
+
+                (
+                    "day_of_week",
+                    days.join(", "),
+                    Some(format!("{:?}", instant.local_weekday())),
+                )
             }
-            _ => true,
+            Self::Reservation(reservation) => (
+                "reservation",
+                format!("{:?}", reservation),
+                state
+                    .reservation
+                    .map(|reservation| format!("{:?}", reservation)),
+            ),
+        };
+
+        RestrictionTrace {
+            restriction,
+            bound,
+            value,
+            is_valid: self.instant_validity(instant, restriction_bound)
+                && self.period_validity(state),
         }
     }
 
     /// Checks if this restriction is valid for `state`.
     pub fn period_validity(&self, state: &PeriodData) -> bool {
         match *self {
+            Self::Reservation(ReservationRestrictionType::Reservation) => {
+                state.reservation.is_some()
+            }
+            Self::Reservation(ReservationRestrictionType::ReservationExpires) => {
+                state.reservation == Some(Reservation::Expired)
+            }
             Self::MinCurrent(min_current) => state
                 .min_current
+                .or(state.current)
                 .map(|current| current >= min_current)
                 .unwrap_or(true),
             Self::MaxCurrent(max_current) => state
                 .max_current
+                .or(state.current)
                 .map(|current| current < max_current)
                 .unwrap_or(true),
             Self::MinPower(min_power) => state
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    ocpi::{cdr::CdrToken, tariff::OcpiTariff, tariff::TariffType},
+    types::time::DateTime,
+};
+
+/// Chooses the tariff that prices a session from the list of provided tariffs.
+///
+/// The pricer asks the selector for the tariff at the start of the session, and at each instant
+/// during the session at which the validity of a tariff starts or ends. A charging period that
+/// references a tariff by its `tariff_id` uses that tariff regardless of the selector.
+///
+/// ```ignore
+/// let pricer = Pricer::with_tariffs(cdr, tariffs, Tz::Europe__Amsterdam)
+///     .tariff_selector(LatestUpdateSelector);
+/// ```
+pub trait TariffSelector: Send + Sync {
+    /// Explain for each of the `tariffs` why it is or isn't selected in `context`. At most one
+    /// candidate is selected, when none is the session can't be priced.
+    fn select(
+        &self,
+        tariffs: &[OcpiTariff],
+        context: &SelectionContext<'_>,
+    ) -> Vec<TariffCandidate>;
+}
+
+/// The session details that are available to a [`TariffSelector`].
+#[derive(Clone, Copy)]
+pub struct SelectionContext<'a> {
+    /// The instant for which a tariff is selected.
+    pub date_time: DateTime,
+    /// The type of tariff that the session prefers, based on its token and charging preference.
+    pub preferred_type: TariffType,
+    /// The token that was used to start the session.
+    pub token: Option<&'a CdrToken>,
+}
+
+/// A tariff that was considered at the start of the session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TariffCandidate {
+    /// Index of the tariff.
+    pub tariff_index: usize,
+    /// The id of the tariff.
+    pub tariff_id: String,
+    /// The type of the tariff.
+    pub tariff_type: Option<TariffType>,
+    /// Why the tariff was or wasn't selected.
+    pub selection: TariffSelection,
+}
+
+/// Why a tariff was or wasn't selected at the start of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TariffSelection {
+    /// Selected, the tariff has the type that the session prefers: `AD_HOC_PAYMENT` for an ad-hoc
+    /// session or the `PROFILE_*` type of its charging preference.
+    Preferred,
+    /// Selected, no valid tariff has the preferred type and this tariff is `REGULAR` or has no
+    /// type.
+    Regular,
+    /// Selected, no valid tariff matches the session and this is the first valid tariff.
+    Fallback,
+    /// Selected by the [`LatestUpdateSelector`], this valid tariff has the most recent
+    /// `last_updated`.
+    LatestUpdate,
+    /// Selected by the [`TokenSelector`], this tariff is assigned to the token of the session.
+    Token,
+    /// Selected by the [`OverrideSelector`], this tariff is used for every session.
+    Override,
+    /// Selected by a selector outside of this crate, for the given reason.
+    Custom(String),
+    /// Skipped, the session starts outside the `start_date_time` and `end_date_time` of the
+    /// tariff.
+    OutsideValidity,
+    /// Skipped, the type of the tariff doesn't match the session.
+    TypeMismatch,
+    /// Skipped, another tariff that matches the session equally well or better is selected.
+    Superseded,
+}
+
+impl TariffSelection {
+    /// Whether the tariff was selected.
+    pub fn is_selected(&self) -> bool {
+        matches!(
+            self,
+            Self::Preferred
+                | Self::Regular
+                | Self::Fallback
+                | Self::LatestUpdate
+                | Self::Token
+                | Self::Override
+                | Self::Custom(_)
+        )
+    }
+}
+
+/// Whether `date_time` lies within the `start_date_time` and `end_date_time` of `tariff`.
+pub fn is_valid(tariff: &OcpiTariff, date_time: DateTime) -> bool {
+    let is_after_start = tariff
+        .start_date_time
+        .map(|start| date_time >= start)
+        .unwrap_or(true);
+    let is_before_end = tariff
+        .end_date_time
+        .map(|end| date_time < end)
+        .unwrap_or(true);
+
+    is_after_start && is_before_end
+}
+
+/// Build the candidates for `tariffs` where the tariff at `selected` is selected for `reason`.
+/// The other tariffs are explained by their validity and whether they match the preferred type.
+///
+/// This helps a [`TariffSelector`] that only decides which tariff to select to explain the
+/// others in the same way as the selectors of this crate.
+pub fn candidates(
+    tariffs: &[OcpiTariff],
+    context: &SelectionContext<'_>,
+    selected: Option<(usize, TariffSelection)>,
+) -> Vec<TariffCandidate> {
+    tariffs
+        .iter()
+        .enumerate()
+        .map(|(tariff_index, tariff)| {
+            let selection = match &selected {
+                Some((index, reason)) if *index == tariff_index => reason.clone(),
+                _ if !is_valid(tariff, context.date_time) => TariffSelection::OutsideValidity,
+                _ if matches_type(tariff, context.preferred_type) => TariffSelection::Superseded,
+                _ => TariffSelection::TypeMismatch,
+            };
+
+            TariffCandidate {
+                tariff_index,
+                tariff_id: tariff.id.clone(),
+                tariff_type: tariff.tariff_type,
+                selection,
+            }
+        })
+        .collect()
+}
+
+/// Whether `tariff` has the `preferred` type, or is a regular tariff.
+fn matches_type(tariff: &OcpiTariff, preferred: TariffType) -> bool {
+    matches!(tariff.tariff_type, None | Some(TariffType::Regular))
+        || tariff.tariff_type == Some(preferred)
+}
+
+/// Selects the first valid tariff of the preferred type. Without such a tariff the first valid
+/// `REGULAR` tariff or tariff without a type is selected, and otherwise the first valid tariff.
+///
+/// This is the selector that the pricer uses by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSelector;
+
+impl TariffSelector for DefaultSelector {
+    fn select(
+        &self,
+        tariffs: &[OcpiTariff],
+        context: &SelectionContext<'_>,
+    ) -> Vec<TariffCandidate> {
+        let preferred = context.preferred_type;
+
+        let valid = || {
+            tariffs
+                .iter()
+                .enumerate()
+                .filter(|(_, tariff)| is_valid(tariff, context.date_time))
+        };
+
+        let selected = valid()
+            .find(|(_, tariff)| {
+                preferred != TariffType::Regular && tariff.tariff_type == Some(preferred)
+            })
+            .map(|(index, _)| (index, TariffSelection::Preferred))
+            .or_else(|| {
+                valid()
+                    .find(|(_, tariff)| {
+                        matches!(tariff.tariff_type, None | Some(TariffType::Regular))
+                    })
+                    .map(|(index, _)| (index, TariffSelection::Regular))
+            })
+            .or_else(|| {
+                valid()
+                    .next()
+                    .map(|(index, _)| (index, TariffSelection::Fallback))
+            });
+
+        candidates(tariffs, context, selected)
+    }
+}
+
+/// Selects the valid tariff that matches the preferred type with the most recent `last_updated`.
+/// Tariffs without `last_updated` are considered older than all others, of equally recent tariffs
+/// the first is selected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatestUpdateSelector;
+
+impl TariffSelector for LatestUpdateSelector {
+    fn select(
+        &self,
+        tariffs: &[OcpiTariff],
+        context: &SelectionContext<'_>,
+    ) -> Vec<TariffCandidate> {
+        let mut selected: Option<(usize, &OcpiTariff)> = None;
+
+        for (index, tariff) in tariffs.iter().enumerate() {
+            if !is_valid(tariff, context.date_time) || !matches_type(tariff, context.preferred_type)
+            {
+                continue;
+            }
+
+            let is_newer = selected
+                .map(|(_, current)| tariff.last_updated > current.last_updated)
+                .unwrap_or(true);
+
+            if is_newer {
+                selected = Some((index, tariff));
+            }
+        }
+
+        let selected = selected.map(|(index, _)| (index, TariffSelection::LatestUpdate));
+
+        candidates(tariffs, context, selected)
+    }
+}
+
+/// Selects the tariff that is assigned to the `uid` of the token that started the session, when
+/// it's valid. Other sessions are handled by the `fallback` selector.
+///
+/// ```ignore
+/// let selector = TokenSelector::new(DefaultSelector).assign("012345678", "tariff-fleet");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TokenSelector<S> {
+    tariffs: HashMap<String, String>,
+    fallback: S,
+}
+
+impl<S: TariffSelector> TokenSelector<S> {
+    /// Create a selector without assigned tariffs that uses `fallback` for all sessions.
+    pub fn new(fallback: S) -> Self {
+        Self {
+            tariffs: HashMap::new(),
+            fallback,
+        }
+    }
+
+    /// Assign the tariff with id `tariff_id` to the token with `uid`.
+    pub fn assign(mut self, uid: &str, tariff_id: &str) -> Self {
+        self.tariffs.insert(uid.to_string(), tariff_id.to_string());
+        self
+    }
+}
+
+impl<S: TariffSelector> TariffSelector for TokenSelector<S> {
+    fn select(
+        &self,
+        tariffs: &[OcpiTariff],
+        context: &SelectionContext<'_>,
+    ) -> Vec<TariffCandidate> {
+        let assigned = context
+            .token
+            .and_then(|token| self.tariffs.get(&token.uid))
+            .and_then(|tariff_id| {
+                tariffs.iter().position(|tariff| {
+                    tariff.id == *tariff_id && is_valid(tariff, context.date_time)
+                })
+            });
+
+        if let Some(index) = assigned {
+            candidates(tariffs, context, Some((index, TariffSelection::Token)))
+        } else {
+            self.fallback.select(tariffs, context)
+        }
+    }
+}
+
+/// Selects the tariff with a fixed id for all sessions regardless of its validity and type, for
+/// example the tariff agreed upon in a contract.
+#[derive(Debug, Clone)]
+pub struct OverrideSelector {
+    tariff_id: String,
+}
+
+impl OverrideSelector {
+    /// Create a selector that always selects the tariff with id `tariff_id`.
+    pub fn new(tariff_id: &str) -> Self {
+        Self {
+            tariff_id: tariff_id.to_string(),
+        }
+    }
+}
+
+impl TariffSelector for OverrideSelector {
+    fn select(
+        &self,
+        tariffs: &[OcpiTariff],
+        context: &SelectionContext<'_>,
+    ) -> Vec<TariffCandidate> {
+        let selected = tariffs
+            .iter()
+            .position(|tariff| tariff.id == self.tariff_id)
+            .map(|index| (index, TariffSelection::Override));
+
+        candidates(tariffs, context, selected)
+    }
+}
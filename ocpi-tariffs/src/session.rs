@@ -1,22 +1,30 @@
 use crate::{
     ocpi::cdr::{Cdr, OcpiCdrDimension, OcpiChargingPeriod},
     types::{
-        electricity::{Ampere, Kw, Kwh},
+        electricity::{Ampere, Kw, Kwh, Percentage},
+        number::Number,
+        period::{DimensionType, PeriodSplit},
         time::DateTime,
     },
+    Error, Result,
 };
 
-use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
 use chrono_tz::Tz;
 
 pub struct ChargeSession {
     pub start_date_time: DateTime,
+    pub currency: String,
     pub periods: Vec<ChargePeriod>,
+    /// The index of the charging period of the CDR at which the total charging duration
+    /// overflowed. The periods from this index onwards are missing.
+    overflow: Option<usize>,
 }
 
 impl ChargeSession {
     pub fn new(cdr: &Cdr, local_timezone: Tz) -> Self {
         let mut periods: Vec<ChargePeriod> = Vec::new();
+        let mut overflow = None;
 
         for (i, period) in cdr.charging_periods.iter().enumerate() {
             let end_date_time = if let Some(next_period) = cdr.charging_periods.get(i + 1) {
@@ -26,23 +34,110 @@ impl ChargeSession {
             };
 
             let next = if let Some(last) = periods.last() {
-                last.next(period, end_date_time)
+                last.next(i, period, end_date_time)
             } else {
                 ChargePeriod::new(local_timezone, period, end_date_time)
             };
 
+            let Some(next) = next else {
+                overflow = Some(i);
+                break;
+            };
+
             periods.push(next);
         }
 
+        // A reservation expires when no charging session follows it.
+        let is_reservation_expired = !periods.iter().any(|p| p.period_data.is_charging());
+
+        if is_reservation_expired {
+            for period in periods.iter_mut() {
+                if period.period_data.reservation.is_some() {
+                    period.period_data.reservation = Some(Reservation::Expired);
+                }
+            }
+        }
+
         Self {
             periods,
             start_date_time: cdr.start_date_time,
+            currency: cdr.currency.clone(),
+            overflow,
+        }
+    }
+
+    /// Check that the total charging duration of the periods of this session doesn't overflow.
+    /// The consistency of the charging periods themselves is checked by the `validation` module.
+    pub fn check(&self) -> Result<()> {
+        if let Some(period_index) = self.overflow {
+            return Err(Error::Overflow {
+                tariff_index: None,
+                period_index,
+                dimension: DimensionType::Time,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Split the periods of this session at the points returned by `boundary`.
+    ///
+    /// The `boundary` closure is called for each period, and for each remainder of a period after
+    /// a split, until it returns `None` or a point that does not lie strictly inside the period.
+    pub fn split_periods<F>(&self, mut boundary: F) -> Result<Vec<ChargePeriod>>
+    where
+        F: FnMut(&ChargePeriod) -> Option<(SplitPoint, PeriodSplit)>,
+    {
+        let mut periods = Vec::new();
+
+        for period in self.periods.iter() {
+            let mut remainder = period.clone();
+
+            while let Some((point, reason)) = boundary(&remainder) {
+                if remainder.fraction(point).is_none() {
+                    break;
+                }
+
+                let (first, second) = remainder.split(point, reason).ok_or(Error::Overflow {
+                    tariff_index: None,
+                    period_index: period.cdr_period_index,
+                    dimension: DimensionType::Time,
+                })?;
+                periods.push(first);
+                remainder = second;
+            }
+
+            periods.push(remainder);
         }
+
+        Ok(periods)
     }
 }
 
+/// A point during a period at which it can be split.
+#[derive(Debug, Clone, Copy)]
+pub enum SplitPoint {
+    /// The period is split at this instant.
+    Instant(DateTime),
+    /// The period is split when the total energy of the session reaches this amount.
+    Energy(Kwh),
+    /// The period is split when the total charging duration of the session reaches this duration.
+    Duration(Duration),
+}
+
 /// Describes the properties of a single charging period.
+#[derive(Clone)]
 pub struct ChargePeriod {
+    /// The index of the charging period in the CDR this period originates from.
+    pub cdr_period_index: usize,
+    /// The identifier of the tariff that is relevant for this period according to the CDR.
+    pub tariff_id: Option<String>,
+    /// Is set when this period is the result of splitting a charging period of the CDR, it
+    /// describes the reason this period starts where it does.
+    pub split: Option<PeriodSplit>,
+    /// Whether the volumes of this period are interpolated from a charging period of the CDR that
+    /// was split.
+    pub is_interpolated: bool,
     /// Holds properties that are valid for the entirety of this period.
     pub period_data: PeriodData,
     /// Holds properties that are valid at start instant of this period.
@@ -53,36 +148,148 @@ pub struct ChargePeriod {
 
 impl ChargePeriod {
     /// Construct a new `ChargePeriod` with zeroed values. Should be the first period in the
-    /// session.
-    fn new(local_timezone: Tz, period: &OcpiChargingPeriod, end_date_time: DateTime) -> Self {
+    /// session. Returns `None` when the total charging duration overflows.
+    fn new(
+        local_timezone: Tz,
+        period: &OcpiChargingPeriod,
+        end_date_time: DateTime,
+    ) -> Option<Self> {
         let charge_state = PeriodData::new(period);
         let start_instant = InstantData::zero(period.start_date_time, local_timezone);
-        let end_instant = start_instant.next(&charge_state, end_date_time);
+        let end_instant = start_instant.next(&charge_state, end_date_time)?;
 
-        Self {
+        Some(Self {
+            cdr_period_index: 0,
+            tariff_id: period.tariff_id.clone(),
+            split: None,
+            is_interpolated: false,
             period_data: charge_state,
             start_instant,
             end_instant,
-        }
+        })
     }
 
     /// Construct a period with the properties of `period` that ends on `end_date_time` which succeeds `self`.
-    fn next(&self, period: &OcpiChargingPeriod, end_date_time: DateTime) -> Self {
+    /// Returns `None` when the total charging duration overflows.
+    fn next(
+        &self,
+        cdr_period_index: usize,
+        period: &OcpiChargingPeriod,
+        end_date_time: DateTime,
+    ) -> Option<Self> {
         let charge_state = PeriodData::new(period);
         let start_instant = self.end_instant.clone();
-        let end_instant = start_instant.next(&charge_state, end_date_time);
+        let end_instant = start_instant.next(&charge_state, end_date_time)?;
 
-        Self {
+        Some(Self {
+            cdr_period_index,
+            tariff_id: period.tariff_id.clone(),
+            split: None,
+            is_interpolated: false,
             period_data: charge_state,
             start_instant,
             end_instant,
+        })
+    }
+
+    /// The fraction of this period that lies before `point`. Returns `None` when the point does
+    /// not lie strictly inside this period.
+    pub fn fraction(&self, point: SplitPoint) -> Option<Number> {
+        let (before, total) = match point {
+            SplitPoint::Instant(date_time) => (
+                Number::from((date_time - self.start_instant.date_time).num_milliseconds()),
+                Number::from(
+                    (self.end_instant.date_time - self.start_instant.date_time).num_milliseconds(),
+                ),
+            ),
+            SplitPoint::Energy(energy) => (
+                Number::from(energy - self.start_instant.total_energy),
+                Number::from(self.period_data.energy?),
+            ),
+            SplitPoint::Duration(duration) => (
+                Number::from((duration - self.start_instant.total_duration).num_milliseconds()),
+                Number::from(self.period_data.duration?.num_milliseconds()),
+            ),
+        };
+
+        let zero = Number::default();
+
+        if before <= zero || before >= total {
+            return None;
         }
+
+        Some(before / total)
+    }
+
+    /// Split this period in two at `point`. The volumes of this period are divided over both
+    /// parts in proportion to their duration, assuming a constant rate during the period. The
+    /// volume that defines `point` is divided exactly. Returns `None` when the total charging
+    /// duration overflows.
+    pub fn split(&self, point: SplitPoint, reason: PeriodSplit) -> Option<(Self, Self)> {
+        let fraction = self.fraction(point).unwrap_or_default();
+        let total = self.end_instant.date_time - self.start_instant.date_time;
+
+        let (mut first_data, mut second_data) = self.period_data.split(fraction)?;
+
+        let date_time = match point {
+            SplitPoint::Instant(date_time) => date_time,
+            SplitPoint::Energy(energy) => {
+                if let Some(total_energy) = self.period_data.energy {
+                    let energy = energy - self.start_instant.total_energy;
+                    first_data.energy = Some(energy);
+                    second_data.energy = Some(total_energy - energy);
+                }
+
+                self.start_instant.date_time + scale_duration(total, fraction)?
+            }
+            SplitPoint::Duration(duration) => {
+                if let Some(total_duration) = self.period_data.duration {
+                    let duration = duration - self.start_instant.total_duration;
+                    first_data.duration = Some(duration);
+                    second_data.duration = Some(total_duration - duration);
+                }
+
+                self.start_instant.date_time + scale_duration(total, fraction)?
+            }
+        };
+
+        let middle_instant = self.start_instant.next(&first_data, date_time)?;
+
+        let first = Self {
+            cdr_period_index: self.cdr_period_index,
+            tariff_id: self.tariff_id.clone(),
+            split: self.split,
+            is_interpolated: true,
+            period_data: first_data,
+            start_instant: self.start_instant.clone(),
+            end_instant: middle_instant.clone(),
+        };
+
+        let second = Self {
+            cdr_period_index: self.cdr_period_index,
+            tariff_id: self.tariff_id.clone(),
+            split: Some(reason),
+            is_interpolated: true,
+            period_data: second_data,
+            start_instant: middle_instant,
+            end_instant: self.end_instant.clone(),
+        };
+
+        Some((first, second))
     }
 }
 
+/// Multiply `duration` by `fraction`, rounded to whole milliseconds. Returns `None` on overflow.
+fn scale_duration(duration: Duration, fraction: Number) -> Option<Duration> {
+    let millis = (Number::from(duration.num_milliseconds()) * fraction).round();
+    millis.try_into().ok().and_then(Duration::try_milliseconds)
+}
+
 /// This describes the properties in the charge session that a valid during a certain period. For
 /// example the `duration` field is the charge duration during a certain charging period.
+#[derive(Clone)]
 pub struct PeriodData {
+    pub current: Option<Ampere>,
     pub max_current: Option<Ampere>,
     pub min_current: Option<Ampere>,
     pub max_power: Option<Kw>,
@@ -90,7 +297,23 @@ pub struct PeriodData {
     pub duration: Option<Duration>,
     pub parking_duration: Option<Duration>,
     pub reservation_duration: Option<Duration>,
+    /// The `ENERGY` volume of the period, or the `ENERGY_IMPORT` volume when the period has no
+    /// `ENERGY` dimension.
     pub energy: Option<Kwh>,
+    pub energy_export: Option<Kwh>,
+    pub energy_import: Option<Kwh>,
+    pub state_of_charge: Option<Percentage>,
+    /// Is set when this period is part of a reservation instead of a charging session.
+    pub reservation: Option<Reservation>,
+}
+
+/// The state of the reservation that a period is part of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reservation {
+    /// The reservation was followed by a charging session.
+    Used,
+    /// The reservation expired before a charging session was started.
+    Expired,
 }
 
 /// This describes the properties in the charge session that are instantaneous. For example
@@ -113,20 +336,22 @@ impl InstantData {
         }
     }
 
-    fn next(&self, state: &PeriodData, date_time: DateTime) -> Self {
+    /// The instant at `date_time` after a period with `state`. Returns `None` when the total
+    /// charging duration overflows.
+    fn next(&self, state: &PeriodData, date_time: DateTime) -> Option<Self> {
         let mut next = self.clone();
 
         next.date_time = date_time;
 
         if let Some(duration) = state.duration {
-            next.total_duration = next.total_duration + duration;
+            next.total_duration = next.total_duration.checked_add(&duration)?;
         }
 
         if let Some(energy) = state.energy {
             next.total_energy += energy;
         }
 
-        next
+        Some(next)
     }
 
     pub fn local_time(&self) -> NaiveTime {
@@ -142,6 +367,39 @@ impl InstantData {
     pub fn local_weekday(&self) -> Weekday {
         self.date_time.with_timezone(&self.local_timezone).weekday()
     }
+
+    /// The first instant after this instant at which the local time equals `time`.
+    pub fn next_local_time(&self, time: NaiveTime) -> Option<DateTime> {
+        let local = self.date_time.with_timezone(&self.local_timezone);
+
+        let date = if local.time() < time {
+            local.date_naive()
+        } else {
+            local.date_naive().succ_opt()?
+        };
+
+        self.local_date_time(date, time)
+    }
+
+    /// The instant at which the local date `date` starts.
+    pub fn local_midnight(&self, date: NaiveDate) -> Option<DateTime> {
+        self.local_date_time(date, NaiveTime::MIN)
+    }
+
+    /// The first instant at which the local date and time reach `date` and `time`. When the local
+    /// time is skipped by a daylight saving time transition this is the end of the gap.
+    fn local_date_time(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime> {
+        const MAX_GAP_MINUTES: i64 = 24 * 60;
+
+        let local = date.and_time(time);
+
+        (0..=MAX_GAP_MINUTES)
+            .find_map(|minutes| {
+                let local = local.checked_add_signed(Duration::minutes(minutes))?;
+                self.local_timezone.from_local_datetime(&local).earliest()
+            })
+            .map(|date_time| date_time.with_timezone(&Utc))
+    }
 }
 
 impl PeriodData {
@@ -149,21 +407,30 @@ impl PeriodData {
         let mut inst = Self {
             parking_duration: None,
             reservation_duration: None,
+            current: None,
             max_current: None,
             min_current: None,
             max_power: None,
             min_power: None,
             duration: None,
             energy: None,
+            energy_export: None,
+            energy_import: None,
+            state_of_charge: None,
+            reservation: None,
         };
 
         for dimension in period.dimensions.iter() {
             match *dimension {
+                OcpiCdrDimension::Current(volume) => inst.current = Some(volume),
                 OcpiCdrDimension::MinCurrent(volume) => inst.min_current = Some(volume),
                 OcpiCdrDimension::MaxCurrent(volume) => inst.max_current = Some(volume),
                 OcpiCdrDimension::MaxPower(volume) => inst.max_power = Some(volume),
                 OcpiCdrDimension::MinPower(volume) => inst.min_power = Some(volume),
                 OcpiCdrDimension::Energy(volume) => inst.energy = Some(volume),
+                OcpiCdrDimension::EnergyExport(volume) => inst.energy_export = Some(volume),
+                OcpiCdrDimension::EnergyImport(volume) => inst.energy_import = Some(volume),
+                OcpiCdrDimension::StateOfCharge(volume) => inst.state_of_charge = Some(volume),
                 OcpiCdrDimension::Time(volume) => {
                     inst.duration = Some(volume.into());
                 }
@@ -172,10 +439,72 @@ impl PeriodData {
                 }
                 OcpiCdrDimension::ReservationTime(volume) => {
                     inst.reservation_duration = Some(volume.into());
+                    inst.reservation = Some(Reservation::Used);
                 }
             }
         }
 
+        if inst.energy.is_none() {
+            inst.energy = inst.energy_import;
+        }
+
         inst
     }
+
+    /// Divide the volumes of this period in a part of `fraction` and the remainder. The
+    /// instantaneous values are valid for both parts. Returns `None` on overflow.
+    fn split(&self, fraction: Number) -> Option<(Self, Self)> {
+        let mut first = self.clone();
+        let mut second = self.clone();
+
+        for (total, first, second) in [
+            (self.energy, &mut first.energy, &mut second.energy),
+            (
+                self.energy_export,
+                &mut first.energy_export,
+                &mut second.energy_export,
+            ),
+            (
+                self.energy_import,
+                &mut first.energy_import,
+                &mut second.energy_import,
+            ),
+        ] {
+            if let Some(total) = total {
+                let first_energy = total * fraction;
+                *first = Some(first_energy);
+                *second = Some(total - first_energy);
+            }
+        }
+
+        // The state of charge is only known at the end of the period.
+        first.state_of_charge = None;
+
+        for (total, first, second) in [
+            (self.duration, &mut first.duration, &mut second.duration),
+            (
+                self.parking_duration,
+                &mut first.parking_duration,
+                &mut second.parking_duration,
+            ),
+            (
+                self.reservation_duration,
+                &mut first.reservation_duration,
+                &mut second.reservation_duration,
+            ),
+        ] {
+            if let Some(total) = total {
+                let first_duration = scale_duration(total, fraction)?;
+                *first = Some(first_duration);
+                *second = Some(total - first_duration);
+            }
+        }
+
+        Some((first, second))
+    }
+
+    /// Whether the EV was connected and charging or parking during this period.
+    fn is_charging(&self) -> bool {
+        self.duration.is_some() || self.energy.is_some() || self.parking_duration.is_some()
+    }
 }
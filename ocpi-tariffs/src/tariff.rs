@@ -2,30 +2,128 @@ use serde::Serialize;
 
 use crate::ocpi::tariff::{OcpiPriceComponent, OcpiTariff, OcpiTariffElement, TariffDimensionType};
 
+use crate::explain::{DimensionTrace, ElementTrace, PeriodTrace, RestrictionTrace};
+use crate::options::RestrictionBound;
+use crate::pricer::PriceLimit;
 use crate::restriction::{collect_restrictions, Restriction};
-use crate::session::ChargePeriod;
-use crate::types::money::Vat;
-use crate::types::{money::Money, time::DateTime};
-
-pub struct Tariffs(Vec<Tariff>);
+use crate::selector::TariffCandidate;
+use crate::selector::{SelectionContext, TariffSelector};
+use crate::session::{ChargePeriod, SplitPoint};
+use crate::types::money::{Price, Vat};
+use crate::types::period::{DimensionType, PeriodSplit};
+use crate::types::rounding::RoundingMode;
+use crate::types::{currency::Currency, money::Money, number::Number, time::DateTime};
+use crate::warning::Warning;
+use crate::{Error, Result};
+
+pub struct Tariffs {
+    tariffs: Vec<Tariff>,
+    /// The tariffs as provided, used by the tariff selector.
+    ocpi_tariffs: Vec<OcpiTariff>,
+}
 
 impl Tariffs {
     pub fn new(tariffs: &[OcpiTariff]) -> Self {
-        Self(tariffs.iter().map(Tariff::new).collect())
+        Self {
+            tariffs: tariffs.iter().map(Tariff::new).collect(),
+            ocpi_tariffs: tariffs.to_vec(),
+        }
+    }
+
+    /// Find the tariff with identifier `id`.
+    ///
+    /// A tariff is identified by its `country_code`, `party_id` and `id`. When both the tariff and
+    /// the lookup have a `country_code` or `party_id`, they have to be equal.
+    pub fn tariff_by_id(
+        &self,
+        id: &str,
+        country_code: Option<&str>,
+        party_id: Option<&str>,
+    ) -> Option<(usize, &Tariff)> {
+        let same = |key: Option<&str>, tariff: Option<&str>| match (key, tariff) {
+            (Some(key), Some(tariff)) => key == tariff,
+            _ => true,
+        };
+
+        let index = self.ocpi_tariffs.iter().position(|tariff| {
+            tariff.id == id
+                && same(country_code, tariff.country_code.as_deref())
+                && same(party_id, tariff.party_id.as_deref())
+        })?;
+
+        self.tariffs.get(index).map(|tariff| (index, tariff))
+    }
+
+    /// Find the tariff that `selector` selects in `context`.
+    pub fn active_tariff(
+        &self,
+        selector: &dyn TariffSelector,
+        context: &SelectionContext<'_>,
+    ) -> Option<(usize, &Tariff)> {
+        let index = self
+            .candidates(selector, context)
+            .into_iter()
+            .find(|candidate| candidate.selection.is_selected())?
+            .tariff_index;
+
+        self.tariffs.get(index).map(|tariff| (index, tariff))
     }
 
-    pub fn active_tariff(&self, start_time: DateTime) -> Option<(usize, &Tariff)> {
-        self.0
+    /// Explain for each tariff why `selector` does or doesn't select it in `context`.
+    pub fn candidates(
+        &self,
+        selector: &dyn TariffSelector,
+        context: &SelectionContext<'_>,
+    ) -> Vec<TariffCandidate> {
+        selector.select(&self.ocpi_tariffs, context)
+    }
+
+    /// Find the first instant after the instant of `context` and before `end_time` at which
+    /// `selector` selects another tariff than the one it selects in `context`.
+    pub fn next_tariff_change(
+        &self,
+        selector: &dyn TariffSelector,
+        context: &SelectionContext<'_>,
+        end_time: DateTime,
+    ) -> Option<DateTime> {
+        let start_time = context.date_time;
+        let active_index = self
+            .active_tariff(selector, context)
+            .map(|(index, _)| index);
+
+        let mut boundaries: Vec<_> = self
+            .ocpi_tariffs
             .iter()
-            .position(|t| t.is_active(start_time))
-            .map(|i| (i, &self.0[i]))
+            .flat_map(|tariff| [tariff.start_date_time, tariff.end_date_time])
+            .flatten()
+            .filter(|&boundary| boundary > start_time && boundary < end_time)
+            .collect();
+
+        boundaries.sort();
+
+        boundaries.into_iter().find(|&boundary| {
+            let context = SelectionContext {
+                date_time: boundary,
+                ..*context
+            };
+
+            let index = self
+                .active_tariff(selector, &context)
+                .map(|(index, _)| index);
+            index.is_some() && index != active_index
+        })
     }
 }
 
 pub struct Tariff {
+    pub id: String,
     elements: Vec<TariffElement>,
-    start_date_time: Option<DateTime>,
-    end_date_time: Option<DateTime>,
+    pub currency: String,
+    pub min_price: Option<Price>,
+    pub max_price: Option<Price>,
+    /// The VAT of the `min_price` and `max_price`, the VAT of the price components when they all
+    /// have the same VAT.
+    pub price_limit_vat: Option<Vat>,
 }
 
 impl Tariff {
@@ -37,18 +135,125 @@ impl Tariff {
             .map(|(element_index, element)| TariffElement::new(element, element_index))
             .collect();
 
+        let mut vats = tariff
+            .elements
+            .iter()
+            .flat_map(|element| &element.price_components)
+            .map(|component| component.vat);
+
+        let first_vat = vats.next().flatten();
+        let price_limit_vat = first_vat.filter(|&vat| vats.all(|other| other == Some(vat)));
+
         Self {
-            start_date_time: tariff.start_date_time,
-            end_date_time: tariff.end_date_time,
+            id: tariff.id.clone(),
+            currency: tariff.currency.clone(),
+            min_price: tariff.min_price,
+            max_price: tariff.max_price,
+            price_limit_vat,
             elements,
         }
     }
 
-    pub fn active_components(&self, period: &ChargePeriod) -> PriceComponents {
+    /// Check that all price components of this tariff, at `tariff_index`, can be priced.
+    pub fn check(&self, tariff_index: usize) -> Result<()> {
+        if self.currency.parse::<Currency>().is_err() {
+            return Err(Error::UnknownCurrency {
+                tariff_index: Some(tariff_index),
+                currency: self.currency.clone(),
+            });
+        }
+
+        for (element_index, element) in self.elements.iter().enumerate() {
+            if let Some(dimension) = element.unsupported_dimension {
+                return Err(Error::UnsupportedDimension {
+                    tariff_index,
+                    element_index,
+                    dimension,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The warnings about the structure of this tariff, at `tariff_index`.
+    pub fn warnings(&self, tariff_index: usize) -> Vec<Warning> {
+        let mut warnings: Vec<_> = self
+            .elements
+            .iter()
+            .enumerate()
+            .flat_map(|(element_index, element)| {
+                element.duplicate_dimensions.iter().map(move |&dimension| {
+                    Warning::DuplicateComponent {
+                        tariff_index,
+                        element_index,
+                        dimension,
+                    }
+                })
+            })
+            .collect();
+
+        // Unit prices can be more precise than the currency, but the price limits are amounts.
+        if let Ok(currency) = self.currency.parse::<Currency>() {
+            let minor_units = currency.minor_units();
+            let has_more_decimals =
+                |amount: Money| amount.round_dp(minor_units, RoundingMode::HalfUp) != amount;
+
+            for (limit, price) in [
+                (PriceLimit::MinPrice, self.min_price),
+                (PriceLimit::MaxPrice, self.max_price),
+            ] {
+                if price.is_some_and(|price| {
+                    has_more_decimals(price.excl_vat) || has_more_decimals(price.incl_vat)
+                }) {
+                    warnings.push(Warning::PriceLimitDecimals {
+                        tariff_index,
+                        limit,
+                        minor_units,
+                    });
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Whether any element of this tariff has a price component for `dimension`.
+    pub fn prices(&self, dimension: DimensionType) -> bool {
+        self.elements
+            .iter()
+            .any(|element| element.components.component(dimension).is_some())
+    }
+
+    /// The price components of `period`, each supplied by the first active element that has a
+    /// component for its dimension. When `trace` is given, the evaluation of the elements is
+    /// recorded in it.
+    pub fn active_components(
+        &self,
+        period: &ChargePeriod,
+        bound: RestrictionBound,
+        mut trace: Option<&mut PeriodTrace>,
+    ) -> PriceComponents {
         let mut components = PriceComponents::new();
 
-        for tariff_element in self.elements.iter() {
-            if !tariff_element.is_active(period) {
+        for (element_index, tariff_element) in self.elements.iter().enumerate() {
+            let is_active = if let Some(trace) = trace.as_deref_mut() {
+                let mut restrictions = Vec::new();
+                let is_active = tariff_element.is_active(period, bound, Some(&mut restrictions));
+
+                trace.elements.push(ElementTrace {
+                    element_index,
+                    is_reservation: tariff_element.is_reservation,
+                    restrictions,
+                    is_active,
+                });
+
+                is_active
+            } else {
+                tariff_element.is_active(period, bound, None)
+            };
+
+            if !is_active {
                 continue;
             }
 
@@ -68,28 +273,101 @@ impl Tariff {
                 components.flat = tariff_element.components.flat.clone();
             }
 
+            if components.reservation_time.is_none() {
+                components.reservation_time = tariff_element.components.reservation_time.clone();
+            }
+
+            if components.reservation_flat.is_none() {
+                components.reservation_flat = tariff_element.components.reservation_flat.clone();
+            }
+
             if components.has_all_components() {
                 break;
             }
         }
 
+        if let Some(trace) = trace {
+            // Every element is considered for a dimension until an active element supplies it.
+            let considered = trace.elements.len();
+
+            trace.dimensions = [
+                DimensionType::Flat,
+                DimensionType::Energy,
+                DimensionType::Time,
+                DimensionType::ParkingTime,
+                DimensionType::ReservationTime,
+                DimensionType::ReservationFlat,
+            ]
+            .into_iter()
+            .map(|dimension| {
+                let element_index = components
+                    .component(dimension)
+                    .map(|component| component.tariff_element_index);
+
+                DimensionTrace {
+                    dimension,
+                    considered: (0..element_index.map(|index| index + 1).unwrap_or(considered))
+                        .collect(),
+                    element_index,
+                }
+            })
+            .collect();
+        }
+
         components
     }
 
-    pub fn is_active(&self, start_time: DateTime) -> bool {
-        let is_after_start = self
-            .start_date_time
-            .map(|s| start_time >= s)
-            .unwrap_or(true);
-        let is_before_end = self.end_date_time.map(|s| start_time < s).unwrap_or(true);
+    /// Find the first point during `period` at which the validity of a restriction of one of
+    /// the elements of this tariff changes, such that the active components differ before and
+    /// after that point.
+    pub fn next_restriction_boundary(
+        &self,
+        period: &ChargePeriod,
+        bound: RestrictionBound,
+    ) -> Option<(SplitPoint, PeriodSplit)> {
+        let mut boundaries: Vec<_> = self
+            .elements
+            .iter()
+            .flat_map(|element| element.restrictions.iter())
+            .filter_map(|restriction| restriction.next_boundary(period))
+            .filter_map(|(point, reason)| Some((period.fraction(point)?, point, reason)))
+            .collect();
+
+        boundaries.sort_by_key(|&(fraction, _, _)| fraction);
+
+        let mut remainder = period.clone();
+
+        for (_, point, reason) in boundaries {
+            if remainder.fraction(point).is_none() {
+                continue;
+            }
+
+            let Some((before, after)) = remainder.split(point, reason) else {
+                return Some((point, reason));
+            };
+
+            if self.active_components(&before, bound, None)
+                != self.active_components(&after, bound, None)
+            {
+                return Some((point, reason));
+            }
+
+            remainder = after;
+        }
 
-        is_after_start && is_before_end
+        None
     }
 }
 
 struct TariffElement {
     restrictions: Vec<Restriction>,
     components: PriceComponents,
+    is_reservation: bool,
+    /// The type of the first price component that can't be priced by this element.
+    unsupported_dimension: Option<TariffDimensionType>,
+    /// The types of the price components that are ignored, since this element already contains
+    /// a component of the same type.
+    duplicate_dimensions: Vec<TariffDimensionType>,
 }
 
 impl TariffElement {
@@ -100,59 +378,93 @@ impl TariffElement {
             Vec::new()
         };
 
+        let is_reservation = restrictions
+            .iter()
+            .any(|restriction| matches!(restriction, Restriction::Reservation(_)));
+
         let mut components = PriceComponents::new();
+        let mut unsupported_dimension = None;
+        let mut duplicate_dimensions = Vec::new();
 
         for ocpi_component in ocpi_element.price_components.iter() {
             let price_component = PriceComponent::new(ocpi_component, element_index);
 
-            match ocpi_component.component_type {
-                TariffDimensionType::Flat => components.flat.get_or_insert(price_component),
-                TariffDimensionType::Time => components.time.get_or_insert(price_component),
-                TariffDimensionType::ParkingTime => {
-                    components.parking.get_or_insert(price_component)
+            let component = match ocpi_component.component_type {
+                // A reservation has no energy or parking volumes.
+                component_type @ (TariffDimensionType::Energy
+                | TariffDimensionType::ParkingTime)
+                    if is_reservation =>
+                {
+                    unsupported_dimension.get_or_insert(component_type);
+                    continue;
                 }
-                TariffDimensionType::Energy => components.energy.get_or_insert(price_component),
+                TariffDimensionType::Flat if is_reservation => &mut components.reservation_flat,
+                TariffDimensionType::Time if is_reservation => &mut components.reservation_time,
+                TariffDimensionType::Flat => &mut components.flat,
+                TariffDimensionType::Time => &mut components.time,
+                TariffDimensionType::ParkingTime => &mut components.parking,
+                TariffDimensionType::Energy => &mut components.energy,
             };
+
+            if component.is_some() {
+                duplicate_dimensions.push(ocpi_component.component_type);
+            } else {
+                *component = Some(price_component);
+            }
         }
 
         Self {
             restrictions,
             components,
+            is_reservation,
+            unsupported_dimension,
+            duplicate_dimensions,
         }
     }
 
-    pub fn is_active(&self, period: &ChargePeriod) -> bool {
-        for restriction in self.restrictions.iter() {
-            if !restriction.instant_validity_exclusive(&period.start_instant) {
-                return false;
-            }
+    /// Whether this element is active during `period`. When `trace` is given, the outcome of
+    /// every restriction is recorded in it, otherwise the check stops at the first restriction
+    /// that doesn't hold.
+    pub fn is_active(
+        &self,
+        period: &ChargePeriod,
+        bound: RestrictionBound,
+        mut trace: Option<&mut Vec<RestrictionTrace>>,
+    ) -> bool {
+        // Elements describe either the costs of a reservation or the costs of charging.
+        let mut is_active = self.is_reservation == period.period_data.reservation.is_some();
 
-            if !restriction.period_validity(&period.period_data) {
-                return false;
-            }
-        }
+        for restriction in self.restrictions.iter() {
+            let Some(trace) = trace.as_deref_mut() else {
+                if !is_active {
+                    break;
+                }
 
-        true
-    }
+                is_active = restriction.instant_validity(&period.start_instant, bound)
+                    && restriction.period_validity(&period.period_data);
+                continue;
+            };
 
-    // use this in the future to validate if a period is still valid when it ends.
-    #[allow(dead_code)]
-    pub fn is_active_at_end(&self, period: &ChargePeriod) -> bool {
-        for restriction in self.restrictions.iter() {
-            if !restriction.instant_validity_inclusive(&period.end_instant) {
-                return false;
-            }
+            let restriction_trace =
+                restriction.explain(&period.start_instant, &period.period_data, bound);
+            is_active &= restriction_trace.is_valid;
+            trace.push(restriction_trace);
         }
 
-        true
+        is_active
     }
 }
 
+#[derive(PartialEq, Eq)]
 pub struct PriceComponents {
     pub flat: Option<PriceComponent>,
     pub energy: Option<PriceComponent>,
     pub parking: Option<PriceComponent>,
     pub time: Option<PriceComponent>,
+    /// The `TIME` component of an element with a reservation restriction.
+    pub reservation_time: Option<PriceComponent>,
+    /// The `FLAT` component of an element with a reservation restriction.
+    pub reservation_flat: Option<PriceComponent>,
 }
 
 impl PriceComponents {
@@ -162,6 +474,37 @@ impl PriceComponents {
             energy: None,
             parking: None,
             time: None,
+            reservation_time: None,
+            reservation_flat: None,
+        }
+    }
+
+    /// The component that prices `dimension`.
+    fn component(&self, dimension: DimensionType) -> Option<&PriceComponent> {
+        match dimension {
+            DimensionType::Flat => self.flat.as_ref(),
+            DimensionType::Energy => self.energy.as_ref(),
+            DimensionType::Time => self.time.as_ref(),
+            DimensionType::ParkingTime => self.parking.as_ref(),
+            DimensionType::ReservationTime => self.reservation_time.as_ref(),
+            DimensionType::ReservationFlat => self.reservation_flat.as_ref(),
+        }
+    }
+
+    /// Convert the prices of all components into another currency using `rate`.
+    pub fn convert(&mut self, rate: Number) {
+        for component in [
+            &mut self.flat,
+            &mut self.energy,
+            &mut self.parking,
+            &mut self.time,
+            &mut self.reservation_time,
+            &mut self.reservation_flat,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            component.price = component.price * rate;
         }
     }
 
@@ -173,7 +516,7 @@ impl PriceComponents {
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, PartialEq, Eq, Serialize)]
 pub struct PriceComponent {
     pub tariff_element_index: usize,
     pub price: Money,
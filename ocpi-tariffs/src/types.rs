@@ -1,3 +1,6 @@
+/// ISO 4217 currencies and their minor units.
+pub mod currency;
+
 /// OCPI Types related to electricity.
 pub mod electricity;
 
@@ -7,5 +10,11 @@ pub mod money;
 /// OCPI Types related to numeric types.
 pub(crate) mod number;
 
+/// Types related to the periods of a report and the dimensions priced in them.
+pub mod period;
+
+/// Types related to the rounding of amounts and volumes in a report.
+pub mod rounding;
+
 /// OCPI Types related to time and durations.
 pub mod time;
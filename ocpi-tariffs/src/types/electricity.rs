@@ -6,7 +6,7 @@ use std::{
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 
-use super::number::Number;
+use super::{number::Number, rounding::RoundingMode};
 
 /// A value of kilo watt hours.
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Default)]
@@ -30,6 +30,10 @@ impl Kwh {
     pub fn with_scale(self) -> Self {
         Self(self.0.with_scale())
     }
+
+    pub(crate) fn round_dp(self, decimals: u32, mode: RoundingMode) -> Self {
+        Self(self.0.round_dp(decimals, mode))
+    }
 }
 
 impl From<Kwh> for Number {
@@ -79,13 +83,30 @@ impl Mul<Number> for Kwh {
 #[serde(transparent)]
 pub struct Kw(Number);
 
+impl Display for Kw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.4}", self.0)
+    }
+}
+
 /// A value of amperes.
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
 #[serde(transparent)]
 pub struct Ampere(Number);
 
+impl Display for Ampere {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.4}", self.0)
+    }
+}
+
 impl From<Number> for Ampere {
     fn from(value: Number) -> Self {
         Self(value)
     }
 }
+
+/// A percentage, for example the state of charge of the battery of an EV.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Percentage(Number);
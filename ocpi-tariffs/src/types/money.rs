@@ -1,12 +1,12 @@
 use std::{
     fmt::Display,
-    ops::{Add, AddAssign, Mul},
+    ops::{Add, AddAssign, Mul, Sub},
 };
 
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 
-use super::{electricity::Kwh, number::Number, time::HoursDecimal};
+use super::{electricity::Kwh, number::Number, rounding::RoundingMode, time::HoursDecimal};
 
 /// A price consisting of a value including VAT, and a value excluding VAT.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
@@ -52,6 +52,28 @@ impl AddAssign for Price {
     }
 }
 
+impl Sub for Price {
+    type Output = Price;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            excl_vat: self.excl_vat - rhs.excl_vat,
+            incl_vat: self.incl_vat - rhs.incl_vat,
+        }
+    }
+}
+
+impl Mul<Number> for Price {
+    type Output = Price;
+
+    fn mul(self, rhs: Number) -> Self::Output {
+        Self {
+            excl_vat: self.excl_vat * rhs,
+            incl_vat: self.incl_vat * rhs,
+        }
+    }
+}
+
 impl Default for Price {
     fn default() -> Self {
         Self::zero()
@@ -59,7 +81,7 @@ impl Default for Price {
 }
 
 /// A monetary amount, the currency is dependant on the specified tariff.
-#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(transparent)]
 pub struct Money(Number);
 
@@ -72,6 +94,10 @@ impl Money {
     pub fn with_scale(self) -> Self {
         Self(self.0.with_scale())
     }
+
+    pub(crate) fn round_dp(self, decimals: u32, mode: RoundingMode) -> Self {
+        Self(self.0.round_dp(decimals, mode))
+    }
 }
 
 impl Add for Money {
@@ -82,6 +108,14 @@ impl Add for Money {
     }
 }
 
+impl Sub for Money {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
 impl Mul<Number> for Money {
     type Output = Money;
 
@@ -155,9 +189,16 @@ impl From<Money> for Number {
     }
 }
 
+/// Formats the amount with 4 decimals, unless a precision is specified. See
+/// [`Currency::format`](super::currency::Currency::format) to format an amount with the minor
+/// units of its currency.
 impl Display for Money {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:.4}", self.0)
+        if f.precision().is_some() {
+            self.0.fmt(f)
+        } else {
+            write!(f, "{:.4}", self.0)
+        }
     }
 }
 
@@ -166,12 +207,29 @@ impl Display for Money {
 #[serde(transparent)]
 pub struct Vat(Number);
 
+impl Vat {
+    /// The factor to multiply an amount excluding VAT with to include this VAT.
+    fn factor(self) -> Number {
+        (self.0 / Number::from(dec!(100))) + Number::from(dec!(1.0))
+    }
+
+    /// Remove this VAT from `amount`, which includes VAT.
+    pub(crate) fn exclude(self, amount: Money) -> Money {
+        let factor = self.factor();
+
+        if factor == Number::default() {
+            return amount;
+        }
+
+        Money(amount.0 / factor)
+    }
+}
+
 impl Mul<Money> for Vat {
     type Output = Money;
 
     fn mul(self, rhs: Money) -> Self::Output {
-        let vat = (self.0 / Number::from(dec!(100))) + Number::from(dec!(1.0));
-        Money(rhs.0 * vat)
+        Money(rhs.0 * self.factor())
     }
 }
 
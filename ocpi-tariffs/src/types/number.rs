@@ -1,22 +1,53 @@
 use std::{
-    fmt::Display,
+    fmt::{self, Display},
     ops::{Add, Div, Mul, Sub},
+    str::FromStr,
 };
 
-use serde::{Deserialize, Deserializer, Serialize};
+use rust_decimal::Decimal;
+use serde::{
+    de::{self, MapAccess, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::rounding::RoundingMode;
+
+/// The key of the map that `serde_json` uses to pass a number with its exact decimal
+/// representation, when its `arbitrary_precision` feature is enabled.
+const ARBITRARY_PRECISION_TOKEN: &str = "$serde_json::private::Number";
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
-pub(crate) struct Number(rust_decimal::Decimal);
+/// A decimal number that is serialized and deserialized without loss of precision when the
+/// `arbitrary_precision` feature of `serde_json` is enabled, see the `arbitrary-precision`
+/// feature of this crate. Otherwise it is serialized as a float.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub(crate) struct Number(Decimal);
 
 impl Number {
     pub(crate) fn ceil(self) -> Self {
         Self(self.0.ceil())
     }
 
+    pub(crate) fn round(self) -> Self {
+        Self(self.0.round())
+    }
+
     pub(crate) fn with_scale(mut self) -> Self {
         self.0.rescale(4);
         self
     }
+
+    pub(crate) fn round_dp(self, decimals: u32, mode: RoundingMode) -> Self {
+        let strategy = match mode {
+            RoundingMode::HalfUp => rust_decimal::RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::HalfEven => rust_decimal::RoundingStrategy::MidpointNearestEven,
+            RoundingMode::Down => rust_decimal::RoundingStrategy::ToZero,
+            RoundingMode::Up => rust_decimal::RoundingStrategy::AwayFromZero,
+        };
+
+        let mut rounded = self.0.round_dp_with_strategy(decimals, strategy);
+        rounded.rescale(decimals);
+        Self(rounded)
+    }
 }
 
 impl<'de> Deserialize<'de> for Number {
@@ -24,14 +55,88 @@ impl<'de> Deserialize<'de> for Number {
     where
         D: Deserializer<'de>,
     {
-        let mut decimal = <rust_decimal::Decimal as Deserialize>::deserialize(deserializer)?;
-        decimal.rescale(4);
-        Ok(Self(decimal))
+        deserialize_decimal(deserializer).map(Self)
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_decimal(&self.0, serializer)
+    }
+}
+
+/// Serialize `decimal` as a JSON number, see [`Number`].
+pub(crate) fn serialize_decimal<S>(decimal: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    use serde::ser::Error;
+
+    let number = serde_json::Number::from_str(&decimal.to_string()).map_err(S::Error::custom)?;
+    number.serialize(serializer)
+}
+
+/// Deserialize a decimal from a JSON number, see [`Number`].
+pub(crate) fn deserialize_decimal<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DecimalVisitor)
+}
+
+struct DecimalVisitor;
+
+impl DecimalVisitor {
+    fn parse<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        Decimal::from_str(value)
+            .or_else(|_e| Decimal::from_scientific(value))
+            .map_err(|_e| E::invalid_value(Unexpected::Str(value), &self))
+    }
+}
+
+impl<'de> Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a decimal number")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+        Ok(value.into())
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+        Ok(value.into())
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+        // The shortest representation of the float, `0.1` instead of its binary approximation,
+        // which keeps the fraction of whole numbers such as `25.0`.
+        self.parse(&format!("{value:?}"))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        self.parse(value)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Decimal, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        if map.next_key::<String>()?.as_deref() != Some(ARBITRARY_PRECISION_TOKEN) {
+            return Err(de::Error::invalid_type(Unexpected::Map, &self));
+        }
+
+        let value = map.next_value::<String>()?;
+        self.parse(&value)
     }
 }
 
-impl From<rust_decimal::Decimal> for Number {
-    fn from(value: rust_decimal::Decimal) -> Self {
+impl From<Decimal> for Number {
+    fn from(value: Decimal) -> Self {
         Self(value)
     }
 }
@@ -89,7 +194,7 @@ impl Sub for Number {
 }
 
 impl Display for Number {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
     }
 }
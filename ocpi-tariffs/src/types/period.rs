@@ -0,0 +1,56 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// The reason a charging period of the CDR was split into multiple periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PeriodSplit {
+    /// Another tariff becomes valid at the start of this period.
+    TariffValidity,
+    /// A `start_time` or `end_time` restriction of the tariff starts or stops being valid at the
+    /// start of this period.
+    TimeOfDay,
+    /// A `start_date`, `end_date` or `day_of_week` restriction of the tariff starts or stops being
+    /// valid at the start of this period.
+    Day,
+    /// The total energy of the session reaches the `min_kwh` or `max_kwh` of a restriction of the
+    /// tariff at the start of this period.
+    Energy,
+    /// The total charging duration of the session reaches the `min_duration` or `max_duration` of
+    /// a restriction of the tariff at the start of this period.
+    Duration,
+}
+
+/// The dimensions that can be priced in a period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DimensionType {
+    /// The flat dimension.
+    Flat,
+    /// The energy dimension.
+    Energy,
+    /// The time dimension.
+    Time,
+    /// The parking time dimension.
+    ParkingTime,
+    /// The reservation time dimension.
+    ReservationTime,
+    /// The flat dimension of a reservation.
+    ReservationFlat,
+}
+
+impl fmt::Display for DimensionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Flat => "FLAT",
+            Self::Energy => "ENERGY",
+            Self::Time => "TIME",
+            Self::ParkingTime => "PARKING_TIME",
+            Self::ReservationTime => "RESERVATION_TIME",
+            Self::ReservationFlat => "RESERVATION_FLAT",
+        };
+
+        f.write_str(s)
+    }
+}
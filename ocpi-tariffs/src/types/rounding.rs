@@ -0,0 +1,148 @@
+use serde::Serialize;
+
+use super::{
+    currency::Currency,
+    electricity::Kwh,
+    money::{Money, Price, Vat},
+    time::HoursDecimal,
+};
+
+/// The policy that a pricer uses to round the amounts and volumes of its report.
+///
+/// The default policy rounds the totals and the `tax_summary` of the report to the minor units of
+/// the currency of the session. Other policies are built from the default, for example to round each
+/// period to 4 decimals:
+///
+/// ```
+/// # use ocpi_tariffs::types::rounding::{Rounding, RoundingScope};
+/// let rounding = Rounding {
+///     money_decimals: Some(4),
+///     scope: RoundingScope::Period,
+///     ..Rounding::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Rounding {
+    /// How a value that lies between two decimals is rounded.
+    pub mode: RoundingMode,
+    /// At which level of the report values are rounded.
+    pub scope: RoundingScope,
+    /// Whether an amount including VAT is calculated from the rounded amount excluding VAT.
+    pub vat: VatRounding,
+    /// The number of decimals of monetary amounts, `None` to round to the minor units of the
+    /// currency of the session.
+    pub money_decimals: Option<u32>,
+    /// The number of decimals of energy volumes in kWh.
+    pub energy_decimals: u32,
+    /// The number of decimals of durations in hours.
+    pub time_decimals: u32,
+}
+
+impl Default for Rounding {
+    fn default() -> Self {
+        Self {
+            mode: RoundingMode::HalfUp,
+            scope: RoundingScope::Total,
+            vat: VatRounding::BeforeVat,
+            money_decimals: None,
+            energy_decimals: 4,
+            time_decimals: 4,
+        }
+    }
+}
+
+/// How a value that lies between two decimals is rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RoundingMode {
+    /// Round to the nearest decimal, halfway values are rounded away from zero.
+    HalfUp,
+    /// Round to the nearest decimal, halfway values are rounded to the even decimal. Also known
+    /// as banker's rounding.
+    HalfEven,
+    /// Round toward zero.
+    Down,
+    /// Round away from zero.
+    Up,
+}
+
+/// At which level of the report values are rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RoundingScope {
+    /// Only the amounts of the tax summary are rounded, the totals keep their full precision.
+    Summary,
+    /// The totals of the session are rounded after summing the unrounded periods.
+    Total,
+    /// The billed volumes and the cost of each dimension are rounded in every period, the totals
+    /// are the sums of these rounded values.
+    Period,
+}
+
+/// The order in which an amount is rounded and VAT is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VatRounding {
+    /// The amount excluding VAT is rounded, and VAT is applied to the rounded amount.
+    BeforeVat,
+    /// VAT is applied to the unrounded amount, and both amounts are rounded.
+    AfterVat,
+}
+
+impl Rounding {
+    /// The number of decimals of monetary amounts when these aren't specified.
+    const DEFAULT_MONEY_DECIMALS: u32 = 4;
+
+    /// This policy with the decimals of monetary amounts set to the minor units of `currency`,
+    /// unless the policy specifies these.
+    pub fn for_currency(self, currency: Currency) -> Self {
+        Self {
+            money_decimals: self.money_decimals.or(Some(currency.minor_units())),
+            ..self
+        }
+    }
+
+    /// Round a monetary `amount`. Without a number of decimals for monetary amounts it's rounded
+    /// to 4 decimals, see [`Rounding::for_currency`].
+    pub fn money(&self, amount: Money) -> Money {
+        let decimals = self.money_decimals.unwrap_or(Self::DEFAULT_MONEY_DECIMALS);
+
+        amount.round_dp(decimals, self.mode)
+    }
+
+    /// Round both amounts of `price` independently.
+    pub fn price(&self, price: Price) -> Price {
+        Price {
+            excl_vat: self.money(price.excl_vat),
+            incl_vat: self.money(price.incl_vat),
+        }
+    }
+
+    /// Round the amount `excl_vat` and calculate the rounded amount including `vat`, in the order
+    /// of the VAT rounding of this policy.
+    pub fn price_with_vat(&self, excl_vat: Money, vat: Option<Vat>) -> Price {
+        let incl_vat = |amount| vat.map(|vat| amount * vat).unwrap_or(amount);
+
+        let rounded = self.money(excl_vat);
+
+        let incl_vat = match self.vat {
+            VatRounding::BeforeVat => self.money(incl_vat(rounded)),
+            VatRounding::AfterVat => self.money(incl_vat(excl_vat)),
+        };
+
+        Price {
+            excl_vat: rounded,
+            incl_vat,
+        }
+    }
+
+    /// Round an energy `volume`.
+    pub fn energy(&self, volume: Kwh) -> Kwh {
+        volume.round_dp(self.energy_decimals, self.mode)
+    }
+
+    /// Round the number of hours of `duration`.
+    pub fn duration(&self, duration: HoursDecimal) -> HoursDecimal {
+        duration.round_dp(self.time_decimals, self.mode)
+    }
+}
@@ -4,14 +4,25 @@ use chrono::Duration;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize, Serializer};
 
-use super::number::Number;
+use super::{number::Number, rounding::RoundingMode};
 
 /// A `chrono` UTC date time.
 pub type DateTime = chrono::DateTime<chrono::Utc>;
 
 /// A generic duration type that converts from and to a decimal amount of hours.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub struct HoursDecimal(pub(crate) Duration);
+///
+/// A deserialized duration keeps the amount of hours as provided, such that it serializes with
+/// the same number of decimals. Durations are equal when their `Duration` is equal.
+#[derive(Debug, Clone, Copy)]
+pub struct HoursDecimal(pub(crate) Duration, Option<Number>);
+
+impl PartialEq for HoursDecimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for HoursDecimal {}
 
 impl<'de> Deserialize<'de> for HoursDecimal {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -20,9 +31,9 @@ impl<'de> Deserialize<'de> for HoursDecimal {
     {
         use serde::de::Error;
 
-        let hours = <Number as serde::Deserialize>::deserialize(deserializer)?;
+        let hours = <Number as Deserialize>::deserialize(deserializer)?;
         let duration = Self::try_from(hours).map_err(|_e| D::Error::custom("overflow"))?;
-        Ok(duration)
+        Ok(Self(duration.0, Some(hours)))
     }
 }
 
@@ -32,6 +43,33 @@ impl Serialize for HoursDecimal {
     }
 }
 
+/// Serialize `duration` as a decimal amount of hours, like the durations of an OCPI CDR. A
+/// deserialized duration serializes as the amount of hours it was deserialized from, otherwise
+/// the duration has millisecond precision.
+pub(crate) fn serialize_hours<S: Serializer>(
+    duration: &HoursDecimal,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if let Some(hours) = duration.1 {
+        return hours.serialize(serializer);
+    }
+
+    let millis = Number::from(duration.0.num_milliseconds());
+    let hours = millis / Number::from(dec!(3_600_000));
+    hours.serialize(serializer)
+}
+
+/// Serialize an optional `duration` as a decimal amount of hours, see [`serialize_hours`].
+pub(crate) fn serialize_optional_hours<S: Serializer>(
+    duration: &Option<HoursDecimal>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serialize_hours(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 impl Display for HoursDecimal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         const SECS_IN_MIN: i64 = 60;
@@ -54,7 +92,7 @@ impl From<HoursDecimal> for Duration {
 
 impl From<Duration> for HoursDecimal {
     fn from(value: Duration) -> Self {
-        Self(value)
+        Self(value, None)
     }
 }
 
@@ -63,8 +101,9 @@ impl TryFrom<Number> for HoursDecimal {
 
     fn try_from(value: Number) -> Result<Self, Self::Error> {
         let millis = value * Number::from(dec!(3_600_000));
-        let duration = Duration::milliseconds(millis.try_into()?);
-        Ok(Self(duration))
+        let duration = Duration::try_milliseconds(millis.try_into()?)
+            .ok_or_else(|| rust_decimal::Error::ConversionTo("Duration".to_string()))?;
+        Ok(Self(duration, None))
     }
 }
 
@@ -87,7 +126,26 @@ impl From<HoursDecimal> for Number {
 
 impl HoursDecimal {
     pub(crate) fn zero() -> Self {
-        Self(Duration::zero())
+        Self(Duration::zero(), None)
+    }
+
+    /// Round the number of hours of this duration to the OCPI specified amount of decimals.
+    pub fn with_scale(self) -> Self {
+        self.round_dp(4, RoundingMode::HalfUp)
+    }
+
+    /// Round the number of hours of this duration to `decimals`, the resulting duration is
+    /// rounded to whole milliseconds.
+    pub(crate) fn round_dp(self, decimals: u32, mode: RoundingMode) -> Self {
+        let millis = Number::from(self.0.num_milliseconds());
+        let hours = (millis / Number::from(dec!(3_600_000))).round_dp(decimals, mode);
+        let millis = (hours * Number::from(dec!(3_600_000))).round();
+
+        i64::try_from(millis)
+            .ok()
+            .and_then(Duration::try_milliseconds)
+            .map(Self::from)
+            .unwrap_or(self)
     }
 }
 
@@ -108,12 +166,12 @@ impl<'de> Deserialize<'de> for SecondsRound {
     {
         use serde::de::Error;
 
-        let seconds = <u64 as serde::Deserialize>::deserialize(deserializer)?;
-        let duration = Duration::seconds(
-            seconds
-                .try_into()
-                .map_err(|_e| D::Error::custom("overflow"))?,
-        );
+        let seconds = <u64 as Deserialize>::deserialize(deserializer)?;
+        let duration = seconds
+            .try_into()
+            .ok()
+            .and_then(Duration::try_seconds)
+            .ok_or_else(|| D::Error::custom("overflow"))?;
 
         Ok(Self(duration))
     }
@@ -157,9 +215,15 @@ impl From<OcpiDate> for chrono::NaiveDate {
 }
 
 /// A OCPI specific local time, without a date.
-#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
 pub struct OcpiTime(chrono::NaiveTime);
 
+impl Serialize for OcpiTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0.format("%H:%M"))
+    }
+}
+
 impl<'de> Deserialize<'de> for OcpiTime {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -0,0 +1,340 @@
+use std::{collections::HashSet, fmt};
+
+use chrono::Duration;
+
+use crate::{
+    ocpi::cdr::{Cdr, CdrDimensionType, OcpiCdrDimension, OcpiChargingPeriod},
+    types::{electricity::Kwh, number::Number, period::DimensionType, time::HoursDecimal},
+    Error, Result,
+};
+
+/// Check the structural consistency of `cdr`. Returns all issues that were found, an empty list
+/// means the CDR can be priced reliably.
+///
+/// The pricer assumes that the charging periods are sorted, lie within the session and contain
+/// each dimension at most once. When these assumptions don't hold the resulting report is not
+/// meaningful.
+pub fn validate_cdr(cdr: &Cdr) -> Vec<CdrIssue> {
+    let mut issues = Vec::new();
+
+    let mut total_energy = Kwh::zero();
+    let mut total_parking_time = Duration::zero();
+
+    for (period_index, period) in cdr.charging_periods.iter().enumerate() {
+        let (energy, parking_time) = check_period(cdr, period_index, period, &mut issues);
+
+        total_energy += energy;
+        total_parking_time = total_parking_time
+            .checked_add(&parking_time)
+            .unwrap_or(Duration::MAX);
+    }
+
+    if total_energy.with_scale() != cdr.total_energy.with_scale() {
+        issues.push(CdrIssue::TotalEnergyMismatch {
+            total_energy: cdr.total_energy,
+            periods_energy: total_energy,
+        });
+    }
+
+    let session_time: HoursDecimal = (cdr.stop_date_time - cdr.start_date_time).into();
+
+    if hours(session_time) != hours(cdr.total_time) {
+        issues.push(CdrIssue::TotalTimeMismatch {
+            total_time: cdr.total_time,
+            session_time,
+        });
+    }
+
+    let periods_parking_time: HoursDecimal = total_parking_time.into();
+
+    if let Some(total_parking_time) = cdr.total_parking_time {
+        if hours(periods_parking_time) != hours(total_parking_time) {
+            issues.push(CdrIssue::TotalParkingTimeMismatch {
+                total_parking_time,
+                periods_parking_time,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Push the issues of the charging `period` at `period_index` of `cdr` to `issues`. Returns the
+/// priced energy and the parking time of the period.
+fn check_period(
+    cdr: &Cdr,
+    period_index: usize,
+    period: &OcpiChargingPeriod,
+    issues: &mut Vec<CdrIssue>,
+) -> (Kwh, Duration) {
+    let previous = period_index
+        .checked_sub(1)
+        .and_then(|index| cdr.charging_periods.get(index));
+
+    if let Some(previous) = previous {
+        if period.start_date_time < previous.start_date_time {
+            issues.push(CdrIssue::UnsortedPeriod { period_index });
+        } else if period.start_date_time == previous.start_date_time {
+            issues.push(CdrIssue::OverlappingPeriod { period_index });
+        }
+    }
+
+    if period.start_date_time < cdr.start_date_time || period.start_date_time > cdr.stop_date_time {
+        issues.push(CdrIssue::PeriodOutsideSession { period_index });
+    }
+
+    let mut dimension_types = HashSet::new();
+    let mut duration = Duration::zero();
+    let mut parking_time = Duration::zero();
+    // Duplicate energy volumes are summed, such that they show up in the total energy check.
+    let mut energy = None;
+    let mut energy_import = None;
+
+    for dimension in period.dimensions.iter() {
+        let dimension_type = dimension.dimension_type();
+
+        if !dimension_types.insert(dimension_type) {
+            issues.push(CdrIssue::DuplicateDimension {
+                period_index,
+                dimension: dimension_type,
+            });
+        }
+
+        let is_negative = match *dimension {
+            OcpiCdrDimension::Energy(volume) => {
+                energy = Some(energy.unwrap_or_else(Kwh::zero) + volume);
+                volume < Kwh::zero()
+            }
+            OcpiCdrDimension::EnergyImport(volume) => {
+                energy_import = Some(energy_import.unwrap_or_else(Kwh::zero) + volume);
+                volume < Kwh::zero()
+            }
+            OcpiCdrDimension::EnergyExport(volume) => volume < Kwh::zero(),
+            OcpiCdrDimension::Time(time) => {
+                duration = duration.checked_add(&time.0).unwrap_or(Duration::MAX);
+                time.0 < Duration::zero()
+            }
+            OcpiCdrDimension::ParkingTime(volume) => {
+                duration = duration.checked_add(&volume.0).unwrap_or(Duration::MAX);
+                parking_time = parking_time.checked_add(&volume.0).unwrap_or(Duration::MAX);
+                volume.0 < Duration::zero()
+            }
+            OcpiCdrDimension::ReservationTime(reservation_time) => {
+                reservation_time.0 < Duration::zero()
+            }
+            _ => false,
+        };
+
+        if is_negative {
+            issues.push(CdrIssue::NegativeVolume {
+                period_index,
+                dimension: dimension_type,
+            });
+        }
+    }
+
+    let end_date_time = cdr
+        .charging_periods
+        .get(period_index + 1)
+        .map(|next| next.start_date_time)
+        .unwrap_or(cdr.stop_date_time);
+
+    let duration: HoursDecimal = duration.into();
+    let period_duration: HoursDecimal = (end_date_time - period.start_date_time).into();
+
+    if hours(duration) > hours(period_duration) {
+        issues.push(CdrIssue::DurationExceedsPeriod {
+            period_index,
+            duration,
+            period_duration,
+        });
+    }
+
+    // The `ENERGY_IMPORT` volume is priced as energy when the period has no `ENERGY` volume.
+    let energy = energy.or(energy_import).unwrap_or_else(Kwh::zero);
+
+    (energy, parking_time)
+}
+
+/// Check that the charging periods of `cdr` are in chronological order, don't start after the
+/// session stopped and don't contain negative volumes. Unlike the other issues found by
+/// [`validate_cdr`] these prevent pricing the CDR at all.
+pub(crate) fn check_cdr(cdr: &Cdr) -> Result<()> {
+    for (period_index, period) in cdr.charging_periods.iter().enumerate() {
+        let mut issues = Vec::new();
+        check_period(cdr, period_index, period, &mut issues);
+
+        for issue in issues {
+            match issue {
+                CdrIssue::UnsortedPeriod { period_index } => {
+                    return Err(Error::InconsistentCdr { period_index });
+                }
+                CdrIssue::PeriodOutsideSession { period_index }
+                    if period.start_date_time > cdr.stop_date_time =>
+                {
+                    return Err(Error::InconsistentCdr { period_index });
+                }
+                CdrIssue::NegativeVolume {
+                    period_index,
+                    dimension,
+                } => {
+                    if let Some(dimension) = priced_dimension(dimension) {
+                        return Err(Error::NegativeVolume {
+                            period_index,
+                            dimension,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The dimension that prices the volume of a CDR dimension, if any.
+fn priced_dimension(dimension: CdrDimensionType) -> Option<DimensionType> {
+    match dimension {
+        CdrDimensionType::Energy | CdrDimensionType::EnergyImport => Some(DimensionType::Energy),
+        CdrDimensionType::Time => Some(DimensionType::Time),
+        CdrDimensionType::ParkingTime => Some(DimensionType::ParkingTime),
+        CdrDimensionType::ReservationTime => Some(DimensionType::ReservationTime),
+        _ => None,
+    }
+}
+
+/// Durations are compared with the precision of the CDR.
+fn hours(duration: HoursDecimal) -> Number {
+    Number::from(duration).with_scale()
+}
+
+/// A structural issue in a CDR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CdrIssue {
+    /// The charging period starts before the previous charging period.
+    UnsortedPeriod {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+    },
+    /// The charging period starts at the same instant as the previous charging period, which
+    /// leaves the previous charging period without duration.
+    OverlappingPeriod {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+    },
+    /// The charging period starts before the start or after the stop of the session.
+    PeriodOutsideSession {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+    },
+    /// The charging period contains a dimension with a negative volume.
+    NegativeVolume {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+        /// The dimension with the negative volume.
+        dimension: CdrDimensionType,
+    },
+    /// The sum of the `TIME` and `PARKING_TIME` volumes of the charging period exceeds the
+    /// duration between its start and the start of the next period.
+    DurationExceedsPeriod {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+        /// The sum of the `TIME` and `PARKING_TIME` volumes.
+        duration: HoursDecimal,
+        /// The duration of the charging period.
+        period_duration: HoursDecimal,
+    },
+    /// The charging period contains the same dimension more than once.
+    DuplicateDimension {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+        /// The dimension that occurs more than once.
+        dimension: CdrDimensionType,
+    },
+    /// The `total_energy` of the CDR differs from the sum of the `ENERGY` volumes, or the
+    /// `ENERGY_IMPORT` volumes of periods without an `ENERGY` volume.
+    TotalEnergyMismatch {
+        /// The `total_energy` of the CDR.
+        total_energy: Kwh,
+        /// The sum of the `ENERGY` volumes of all charging periods.
+        periods_energy: Kwh,
+    },
+    /// The `total_time` of the CDR differs from the duration between the start and stop of the
+    /// session.
+    TotalTimeMismatch {
+        /// The `total_time` of the CDR.
+        total_time: HoursDecimal,
+        /// The duration between the start and stop of the session.
+        session_time: HoursDecimal,
+    },
+    /// The `total_parking_time` of the CDR differs from the sum of the `PARKING_TIME` volumes.
+    TotalParkingTimeMismatch {
+        /// The `total_parking_time` of the CDR.
+        total_parking_time: HoursDecimal,
+        /// The sum of the `PARKING_TIME` volumes of all charging periods.
+        periods_parking_time: HoursDecimal,
+    },
+}
+
+impl fmt::Display for CdrIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsortedPeriod { period_index } => write!(
+                f,
+                "Charging period {period_index} starts before the previous period"
+            ),
+            Self::OverlappingPeriod { period_index } => write!(
+                f,
+                "Charging period {period_index} starts at the same time as the previous period"
+            ),
+            Self::PeriodOutsideSession { period_index } => write!(
+                f,
+                "Charging period {period_index} starts outside of the session"
+            ),
+            Self::NegativeVolume {
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Charging period {period_index} has a negative `{dimension}` volume"
+            ),
+            Self::DurationExceedsPeriod {
+                period_index,
+                duration,
+                period_duration,
+            } => write!(
+                f,
+                "Charging period {period_index} has {duration} of time and parking time, which exceeds its duration of {period_duration}"
+            ),
+            Self::DuplicateDimension {
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Charging period {period_index} contains the `{dimension}` dimension more than once"
+            ),
+            Self::TotalEnergyMismatch {
+                total_energy,
+                periods_energy,
+            } => write!(
+                f,
+                "The total energy {total_energy} differs from the energy of the periods {periods_energy}"
+            ),
+            Self::TotalTimeMismatch {
+                total_time,
+                session_time,
+            } => write!(
+                f,
+                "The total time {total_time} differs from the duration of the session {session_time}"
+            ),
+            Self::TotalParkingTimeMismatch {
+                total_parking_time,
+                periods_parking_time,
+            } => write!(
+                f,
+                "The total parking time {total_parking_time} differs from the parking time of the periods {periods_parking_time}"
+            ),
+        }
+    }
+}
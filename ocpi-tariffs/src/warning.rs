@@ -0,0 +1,203 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{
+    ocpi::{
+        cdr::{Cdr, CdrDimensionType, OcpiCdrDimension},
+        tariff::TariffDimensionType,
+        v211::cdr::FLAT_VOLUME_FIELD,
+    },
+    pricer::PriceLimit,
+    types::{electricity::Kwh, number::deserialize_decimal, period::DimensionType},
+};
+
+/// Input that is suspicious, but that can still be priced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "type")]
+pub enum Warning {
+    /// A tariff element contains more than one price component of the same type, only the first
+    /// component is used.
+    DuplicateComponent {
+        /// Index of the tariff.
+        tariff_index: usize,
+        /// Index of the element in the tariff.
+        element_index: usize,
+        /// The type of the ignored price component.
+        dimension: TariffDimensionType,
+    },
+    /// A price component of an OCPI 3.0 tariff includes taxes, but has no VAT percentage. Its
+    /// price is used as price excluding VAT.
+    MissingVat {
+        /// Index of the tariff.
+        tariff_index: usize,
+        /// Index of the element in the tariff.
+        element_index: usize,
+        /// The type of the price component.
+        dimension: TariffDimensionType,
+    },
+    /// A period has a volume of a dimension that the tariff prices, but none of the elements that
+    /// are active during the period has a price component for it. The volume is priced at zero.
+    UnpricedVolume {
+        /// Index of the tariff that priced the period.
+        tariff_index: usize,
+        /// Index of the period in the report.
+        period_index: usize,
+        /// The dimension of the volume.
+        dimension: DimensionType,
+    },
+    /// A period has a volume of a dimension that none of the elements of the tariff has a price
+    /// component for. The volume is priced at zero. Reported once per tariff and dimension, for
+    /// the first period that has such a volume.
+    UntariffedVolume {
+        /// Index of the tariff that priced the period.
+        tariff_index: usize,
+        /// Index of the period in the report.
+        period_index: usize,
+        /// The dimension of the volume.
+        dimension: DimensionType,
+    },
+    /// A charging period of the CDR contains a volume that isn't used to price the session. For
+    /// example an `ENERGY_EXPORT` volume, or an `ENERGY_IMPORT` volume next to an `ENERGY`
+    /// volume.
+    UnusedVolume {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+        /// The dimension of the volume.
+        dimension: CdrDimensionType,
+    },
+    /// A charging period of an OCPI 2.1.1 CDR has a `FLAT` volume, which doesn't exist in OCPI
+    /// 2.2.1. The volume isn't used to price the session, flat fees are charged once per session.
+    DiscardedFlatVolume {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+    },
+    /// A charging period of the CDR references a tariff that isn't provided. The period is
+    /// priced using the tariff that is selected for it instead.
+    UnknownTariff {
+        /// Index of the charging period in the CDR.
+        period_index: usize,
+        /// The referenced tariff identifier.
+        tariff_id: String,
+    },
+    /// The `min_price` or `max_price` of a tariff has more decimals than the minor unit of the
+    /// currency of the tariff. The limit is used as provided.
+    PriceLimitDecimals {
+        /// Index of the tariff.
+        tariff_index: usize,
+        /// The price limit with too many decimals.
+        limit: PriceLimit,
+        /// The number of decimals of the minor unit of the currency.
+        minor_units: u32,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateComponent {
+                tariff_index,
+                element_index,
+                dimension,
+            } => write!(
+                f,
+                "Element {element_index} of tariff {tariff_index} contains more than one `{dimension}` price component, only the first is used"
+            ),
+            Self::MissingVat {
+                tariff_index,
+                element_index,
+                dimension,
+            } => write!(
+                f,
+                "Element {element_index} of tariff {tariff_index} has a `{dimension}` price component that includes taxes without a VAT percentage, the price is used excluding VAT"
+            ),
+            Self::UnpricedVolume {
+                tariff_index,
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Period {period_index} has a `{dimension}` volume, but no active element of tariff {tariff_index} prices it"
+            ),
+            Self::UntariffedVolume {
+                tariff_index,
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Period {period_index} has a `{dimension}` volume, but tariff {tariff_index} has no price component for it"
+            ),
+            Self::UnusedVolume {
+                period_index,
+                dimension,
+            } => write!(
+                f,
+                "Charging period {period_index} has a `{dimension}` volume that isn't priced"
+            ),
+            Self::DiscardedFlatVolume { period_index } => write!(
+                f,
+                "Charging period {period_index} has an OCPI 2.1.1 `FLAT` volume that isn't priced"
+            ),
+            Self::UnknownTariff {
+                period_index,
+                tariff_id,
+            } => write!(
+                f,
+                "Charging period {period_index} references tariff `{tariff_id}` that isn't provided, the selected tariff is used instead"
+            ),
+            Self::PriceLimitDecimals {
+                tariff_index,
+                limit,
+                minor_units,
+            } => {
+                let field = match limit {
+                    PriceLimit::MinPrice => "min_price",
+                    PriceLimit::MaxPrice => "max_price",
+                };
+
+                write!(
+                    f,
+                    "The `{field}` of tariff {tariff_index} has more than the {minor_units} decimals of its currency"
+                )
+            }
+        }
+    }
+}
+
+/// Find the volumes in the charging periods of `cdr` that aren't used to price the session.
+pub(crate) fn unused_volumes(cdr: &Cdr) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for (period_index, period) in cdr.charging_periods.iter().enumerate() {
+        let has_energy = period
+            .dimensions
+            .iter()
+            .any(|dimension| matches!(dimension, OcpiCdrDimension::Energy(_)));
+
+        for dimension in &period.dimensions {
+            let is_unused = match *dimension {
+                OcpiCdrDimension::EnergyExport(volume) => volume != Kwh::zero(),
+                OcpiCdrDimension::EnergyImport(volume) => has_energy && volume != Kwh::zero(),
+                _ => false,
+            };
+
+            if is_unused {
+                warnings.push(Warning::UnusedVolume {
+                    period_index,
+                    dimension: dimension.dimension_type(),
+                });
+            }
+        }
+
+        let flat_volume = period
+            .unknown_fields
+            .get(FLAT_VOLUME_FIELD)
+            .and_then(|volume| deserialize_decimal(volume).ok());
+
+        if flat_volume.is_some_and(|volume| !volume.is_zero()) {
+            warnings.push(Warning::DiscardedFlatVolume { period_index });
+        }
+    }
+
+    warnings
+}
@@ -1,17 +1,25 @@
+// Each test file uses a subset of these helpers.
+#![allow(dead_code)]
+
 use std::{
     fs::{read_dir, File},
     path::PathBuf,
+    str::FromStr,
 };
 
 use chrono_tz::Tz;
 use ocpi_tariffs::{
     ocpi::{cdr::Cdr, tariff::OcpiTariff},
     pricer::Pricer,
+    types::rounding::{Rounding, RoundingScope},
 };
+use rust_decimal::Decimal;
+use serde_json::Value;
 
 pub struct JsonTest {
     pub path: PathBuf,
-    pub tariff: OcpiTariff,
+    /// The tariff to price the CDRs with, when absent the tariffs contained in the CDRs are used.
+    pub tariff: Option<OcpiTariff>,
     pub cdrs: Vec<(String, Cdr)>,
 }
 
@@ -26,7 +34,7 @@ pub fn collect_json_tests() -> Result<Vec<JsonTest>, Box<dyn std::error::Error>>
         }
 
         let mut tariff = None;
-        let mut cdrs = Vec::new();
+        let mut cdrs: Vec<(String, Cdr)> = Vec::new();
 
         for json_file in read_dir(&test_dir_path)? {
             let file_path = json_file?.path();
@@ -46,9 +54,15 @@ pub fn collect_json_tests() -> Result<Vec<JsonTest>, Box<dyn std::error::Error>>
             }
         }
 
+        if tariff.is_none() && cdrs.iter().any(|(_, cdr)| cdr.tariffs.is_empty()) {
+            panic!(
+                "no tariff.json in test directory {:?} and not all CDRs contain tariffs",
+                test_dir_path
+            );
+        }
+
         tests.push(JsonTest {
-            tariff: tariff
-                .unwrap_or_else(|| panic!("no tariff.json in test directory {:?}", test_dir_path)),
+            tariff,
             cdrs,
             path: test_dir_path,
         });
@@ -57,6 +71,33 @@ pub fn collect_json_tests() -> Result<Vec<JsonTest>, Box<dyn std::error::Error>>
     Ok(tests)
 }
 
+/// Compare two JSON values, where numbers are equal when they have the same numeric value
+/// regardless of their representation. For example `1.50` equals `1.5`.
+pub fn numerically_eq(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => {
+            Decimal::from_str(&left.to_string()).ok() == Decimal::from_str(&right.to_string()).ok()
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right.iter())
+                    .all(|(left, right)| numerically_eq(left, right))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left.iter().all(|(key, left)| {
+                    right
+                        .get(key)
+                        .map(|right| numerically_eq(left, right))
+                        .unwrap_or(false)
+                })
+        }
+        (left, right) => left == right,
+    }
+}
+
 #[macro_export]
 macro_rules! tariff {
     ($name:literal) => {
@@ -69,9 +110,42 @@ macro_rules! tariff {
     };
 }
 
-pub fn validate_cdr(cdr: Cdr, tariff: OcpiTariff) -> Result<(), ocpi_tariffs::Error> {
-    let pricer = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC);
-    let report = pricer.build_report()?;
+/// Deserialize the JSON fixture `tests/fixtures/$name`.
+#[macro_export]
+macro_rules! fixture {
+    ($name:literal) => {
+        serde_json::from_str(include_str!(concat!("fixtures/", $name))).unwrap()
+    };
+}
+
+#[macro_export]
+macro_rules! cdr {
+    ($name:literal, $cdr:literal) => {
+        serde_json::from_str::<'_, ocpi_tariffs::ocpi::cdr::Cdr>(include_str!(concat!(
+            "../resources/",
+            $name,
+            "/",
+            $cdr,
+            ".json"
+        )))
+        .unwrap()
+    };
+}
+
+pub fn validate_cdr(cdr: Cdr, tariff: Option<OcpiTariff>) -> Result<(), ocpi_tariffs::Error> {
+    let pricer = if let Some(tariff) = tariff {
+        Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+    } else {
+        Pricer::new(&cdr, Tz::UTC)
+    };
+
+    // The example CDRs keep the full precision of their totals.
+    let report = pricer
+        .rounding(Rounding {
+            scope: RoundingScope::Summary,
+            ..Rounding::default()
+        })
+        .build_report()?;
 
     assert_eq!(cdr.total_cost, report.total_cost.with_scale(), "total_cost");
 
@@ -0,0 +1,135 @@
+use chrono_tz::Tz;
+use ocpi_tariffs::{
+    explain::PeriodTrace,
+    interpretation::interpret,
+    ocpi::{cdr::Cdr, tariff::OcpiTariff},
+    options::{PricerOptions, RestrictionBound, RestrictionCheck},
+    pricer::Pricer,
+    types::{period::DimensionType, rounding::Rounding},
+};
+
+mod common;
+
+#[test]
+fn test_interpret() {
+    let tariff: OcpiTariff = fixture!("interpret/tariff.json");
+
+    let cdr: Cdr = fixture!("interpret/cdr.json");
+
+    let interpretations = interpret(&cdr, Some(std::slice::from_ref(&tariff)), Tz::UTC);
+    assert_eq!(interpretations.len(), 240);
+
+    // The first reading is the default of the pricer.
+    let strict = &interpretations[0];
+    assert_eq!(strict.options, PricerOptions::strict());
+    assert_eq!(strict.rounding, Rounding::default());
+    assert!(!strict.matches(&cdr));
+
+    // The CDR was priced by the element that is active at the start of the charging period, or
+    // the period split at 15:00 is still within the `end_time` of 15:00.
+    assert!(interpretations
+        .iter()
+        .filter(|interpretation| interpretation.matches(&cdr))
+        .all(|interpretation| interpretation.options.restriction_check
+            == RestrictionCheck::PeriodStart
+            || interpretation.options.restriction_bound == RestrictionBound::Inclusive));
+    assert!(interpretations
+        .iter()
+        .any(|interpretation| interpretation.matches(&cdr)));
+
+    // The times are compared as hours with the OCPI specified amount of decimals.
+    let mut rounded_cdr = cdr.clone();
+    rounded_cdr.total_time = serde_json::from_str("0.99996").unwrap();
+    assert!(interpretations
+        .iter()
+        .filter(|interpretation| interpretation.matches(&cdr))
+        .all(|interpretation| interpretation.matches(&rounded_cdr)));
+}
+
+#[test]
+fn test_explain() {
+    let tariff: OcpiTariff = fixture!("explain/tariff.json");
+
+    let cdr: Cdr = fixture!("explain/cdr.json");
+
+    let pricer = || Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC);
+
+    assert!(pricer().build_report().unwrap().trace.is_none());
+
+    let report = pricer().explain(true).build_report().unwrap();
+    let trace = report.trace.unwrap();
+
+    // The period is split when the `max_kwh` of 5 kWh is reached at 14:45. The `end_time` of
+    // 15:00 changes nothing, since the first element is no longer active by then.
+    assert_eq!(trace.len(), report.periods.len());
+    assert_eq!(trace.len(), 2);
+
+    let first = &trace[0];
+    assert_eq!(first.tariff_id, "1");
+    assert!(first.elements[0].is_active);
+
+    let end_time = &first.elements[0].restrictions[0];
+    assert_eq!(end_time.restriction, "end_time");
+    assert_eq!(end_time.bound, "15:00:00");
+    assert_eq!(end_time.value.as_deref(), Some("14:30:00"));
+    assert!(end_time.is_valid);
+
+    let energy = |period: &PeriodTrace| {
+        period
+            .dimensions
+            .iter()
+            .find(|dimension| dimension.dimension == DimensionType::Energy)
+            .unwrap()
+            .clone()
+    };
+
+    assert_eq!(energy(first).considered, [0]);
+    assert_eq!(energy(first).element_index, Some(0));
+
+    let second = &trace[1];
+    assert!(!second.elements[0].is_active);
+
+    let max_kwh = &second.elements[0].restrictions[1];
+    assert_eq!(max_kwh.restriction, "max_kwh");
+    assert_eq!(max_kwh.bound, "5.0000");
+    assert_eq!(max_kwh.value.as_deref(), Some("5.0000"));
+    assert!(!max_kwh.is_valid);
+
+    assert_eq!(energy(second).considered, [0, 1]);
+    assert_eq!(energy(second).element_index, Some(1));
+}
+
+#[test]
+fn test_restriction_bound() {
+    let tariff: OcpiTariff = fixture!("explain/tariff.json");
+
+    let cdr: Cdr = fixture!("explain/cdr.json");
+
+    let report = Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC)
+        .options(PricerOptions {
+            restriction_bound: RestrictionBound::Inclusive,
+            ..PricerOptions::strict()
+        })
+        .explain(true)
+        .build_report()
+        .unwrap();
+
+    let trace = report.trace.unwrap();
+
+    // At 14:45 the session reaches exactly 5 kWh, which is still within the `max_kwh`, so the
+    // period isn't split there.
+    assert_eq!(trace.len(), 2);
+
+    let first = &trace[0];
+    assert!(first.elements[0].is_active);
+
+    // The second period starts at 15:00, but has exceeded the `max_kwh`.
+    let second = &trace[1];
+    assert!(second.elements[0].restrictions[0].is_valid);
+    assert_eq!(
+        second.elements[0].restrictions[0].value.as_deref(),
+        Some("15:00:00")
+    );
+    assert!(!second.elements[0].restrictions[1].is_valid);
+    assert!(!second.elements[0].is_active);
+}
@@ -0,0 +1,157 @@
+use chrono_tz::Tz;
+use ocpi_tariffs::{
+    ocpi::{cdr::Cdr, tariff::OcpiTariff},
+    pricer::Pricer,
+    types::{
+        money::Money,
+        rounding::{Rounding, RoundingScope},
+    },
+};
+use serde::{
+    de::{value::Error, IntoDeserializer},
+    Deserialize,
+};
+use serde_json::Value;
+
+mod common;
+
+#[test]
+fn test_json_files() {
+    let mut should_panic = false;
+
+    for json_test in common::collect_json_tests().unwrap() {
+        let tariff = json_test.tariff;
+
+        eprintln!("\ntesting directory {:?}", json_test.path);
+
+        for (name, cdr) in json_test.cdrs {
+            eprint!("  testing json cdr `{}`: ", name);
+
+            let result = std::panic::catch_unwind(|| {
+                common::validate_cdr(cdr, tariff.clone()).unwrap();
+            });
+
+            if result.is_err() {
+                should_panic = true;
+            } else {
+                eprintln!("success");
+            }
+        }
+    }
+
+    if should_panic {
+        panic!("not all json tests succeeded")
+    }
+}
+
+#[test]
+fn test_tariff_round_trip() {
+    let resources = concat!(env!("CARGO_MANIFEST_DIR"), "/resources");
+
+    for test_dir in std::fs::read_dir(resources).unwrap() {
+        let path = test_dir.unwrap().path().join("tariff.json");
+
+        let Ok(json) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let value: Value = serde_json::from_str(&json).unwrap();
+        let tariff: OcpiTariff = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(serde_json::to_value(&tariff).unwrap(), value, "{path:?}");
+    }
+}
+
+#[test]
+fn test_tariff_round_trip_unknown_fields() {
+    let json = include_str!("fixtures/unknown_fields/tariff.json");
+
+    let value: Value = serde_json::from_str(json).unwrap();
+    let tariff: OcpiTariff = serde_json::from_str(json).unwrap();
+
+    assert_eq!(serde_json::to_value(&tariff).unwrap(), value);
+}
+
+#[test]
+fn test_tariff_round_trip_empty_fields() {
+    let json = include_str!("fixtures/empty_fields/tariff.json");
+
+    let value: Value = serde_json::from_str(json).unwrap();
+    let tariff: OcpiTariff = serde_json::from_str(json).unwrap();
+
+    assert_eq!(serde_json::to_value(&tariff).unwrap(), value);
+}
+
+#[test]
+fn test_cdr_round_trip() {
+    let resources = concat!(env!("CARGO_MANIFEST_DIR"), "/resources");
+
+    for test_dir in std::fs::read_dir(resources).unwrap() {
+        for json_file in std::fs::read_dir(test_dir.unwrap().path()).unwrap() {
+            let path = json_file.unwrap().path();
+
+            if path.file_stem().unwrap() == "tariff" {
+                continue;
+            }
+
+            let json = std::fs::read_to_string(&path).unwrap();
+
+            let value: Value = serde_json::from_str(&json).unwrap();
+            let cdr: Cdr = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(serde_json::to_value(&cdr).unwrap(), value, "{path:?}");
+        }
+    }
+}
+
+#[test]
+fn test_number_without_arbitrary_precision() {
+    // Without the `arbitrary_precision` feature of `serde_json` numbers are passed as floats and
+    // integers, which are parsed using their shortest representation.
+    let price = Money::deserialize(IntoDeserializer::<Error>::into_deserializer(0.1_f64)).unwrap();
+    assert_eq!(price.to_string(), "0.1000");
+
+    let price = Money::deserialize(IntoDeserializer::<Error>::into_deserializer(2_u64)).unwrap();
+    assert_eq!(price.to_string(), "2.0000");
+}
+
+#[test]
+fn test_price_precision() {
+    let tariff = tariff!("precise_price");
+    let cdr = cdr!("precise_price", "cdr1");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .rounding(Rounding {
+            scope: RoundingScope::Summary,
+            ..Rounding::default()
+        })
+        .build_report()
+        .unwrap();
+
+    // The price of 0.12344 used to be rounded to 0.1234 when parsed, pricing 20 kWh at 2.468.
+    assert_eq!(
+        report.total_energy_cost.with_scale().excl_vat.to_string(),
+        "2.4688"
+    );
+
+    // Amounts in the report used to be serialized as strings.
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json["total_cost"]["excl_vat"].is_number());
+}
+
+#[test]
+fn test_report_duration_format() {
+    let tariff = tariff!("simple_025kwh");
+    let cdr = cdr!("simple_025kwh", "cdr1");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    // The durations of a CDR serialize as decimal hours, those of a report as `HH:MM:SS`.
+    assert_eq!(serde_json::to_value(&cdr).unwrap()["total_time"], 1);
+    assert_eq!(
+        serde_json::to_value(&report).unwrap()["total_time"],
+        "01:00:00"
+    );
+}
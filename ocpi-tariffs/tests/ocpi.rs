@@ -0,0 +1,150 @@
+use chrono_tz::Tz;
+use ocpi_tariffs::{
+    ocpi::{self, cdr::Cdr, tariff::OcpiTariff, tariff::TariffDimensionType, v211, v3, Version},
+    options::PricerOptions,
+    pricer::Pricer,
+    warning::Warning,
+};
+
+mod common;
+
+#[test]
+fn test_ocpi_211() {
+    let json = include_str!("fixtures/ocpi_211/cdr.json");
+
+    let cdr = ocpi::cdr_from_str(json, Version::V211).unwrap();
+
+    assert_eq!(cdr.tariffs[0].id, "12");
+    assert_eq!(cdr.cdr_location.as_ref().unwrap().evse_uid, "3256");
+    assert_eq!(cdr.charging_periods[0].dimensions.len(), 1);
+
+    // OCPI 2.1.1 has no VAT, the total cost is used as if no VAT applies.
+    assert_eq!(cdr.total_cost.excl_vat.to_string(), "5.0000");
+    assert_eq!(cdr.total_cost.incl_vat, cdr.total_cost.excl_vat);
+
+    // The `FLAT` volume is kept, but isn't priced.
+    assert_eq!(cdr.charging_periods[0].unknown_fields["flat_volume"], 1);
+
+    let report = Pricer::new(&cdr, Tz::UTC).build_report().unwrap();
+
+    assert_eq!(
+        report.total_cost.with_scale().excl_vat.to_string(),
+        "5.0000"
+    );
+    assert_eq!(
+        report.warnings,
+        [Warning::DiscardedFlatVolume { period_index: 0 }]
+    );
+
+    let cdr: v211::cdr::Cdr = cdr.into();
+    let value = serde_json::to_value(&cdr).unwrap();
+
+    assert_eq!(value["auth_id"], "DE8ACC12E46L89");
+    assert_eq!(
+        value["location"]["evses"][0]["evse_id"],
+        "BE*BEC*E041503003"
+    );
+    assert_eq!(
+        value["charging_periods"][0]["dimensions"][1],
+        serde_json::json!({ "type": "FLAT", "volume": 1 })
+    );
+    assert!(common::numerically_eq(
+        &value["total_cost"],
+        &serde_json::json!(5.0)
+    ));
+}
+
+#[test]
+fn test_ocpi_211_location_without_connector() {
+    let json = include_str!("fixtures/ocpi_211/cdr_without_connector.json");
+
+    // OCPI 2.2.1 requires an `evse_id` and a connector, the location is kept as unknown field.
+    let cdr = ocpi::cdr_from_str(json, Version::V211).unwrap();
+
+    assert!(cdr.cdr_location.is_none());
+    assert_eq!(cdr.unknown_fields["location"]["evses"][0]["uid"], "3256");
+
+    let cdr: v211::cdr::Cdr = cdr.into();
+    let value = serde_json::to_value(&cdr).unwrap();
+
+    assert_eq!(value["location"]["id"], "LOC1");
+    assert!(value["location"]["evses"][0]["evse_id"].is_null());
+}
+
+#[test]
+fn test_ocpi_221_tariff_into_211() {
+    let tariff = tariff!("simple_025kwh");
+    let tariff: v211::tariff::OcpiTariff = tariff.into();
+    let value = serde_json::to_value(&tariff).unwrap();
+
+    assert!(value.get("country_code").is_none());
+    assert!(value["elements"][0]["price_components"][0]
+        .get("vat")
+        .is_none());
+
+    let json = serde_json::to_string(&tariff).unwrap();
+    let tariff = ocpi::tariff_from_str(&json, Version::V211).unwrap();
+
+    assert_eq!(tariff.id, "16");
+    assert!(tariff.elements[0].price_components[0].vat.is_none());
+}
+
+#[test]
+fn test_ocpi_3_tariff() {
+    let tariff: v3::tariff::OcpiTariff = fixture!("ocpi_3/tariff.json");
+
+    let cdr: Cdr = fixture!("ocpi_3/cdr.json");
+
+    let report = Pricer::with_v3_tariffs(&cdr, &[tariff], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.options, PricerOptions::ocpi_3());
+
+    // Both elements price 15 minutes, which are billed as 30 minutes each.
+    assert_eq!(report.periods.len(), 2);
+    assert_eq!(report.billed_charging_time.to_string(), "01:00:00");
+    assert_eq!(
+        report.total_time_cost.with_scale().excl_vat.to_string(),
+        "1.5000"
+    );
+    assert_eq!(
+        report.total_time_cost.with_scale().incl_vat.to_string(),
+        "1.8000"
+    );
+
+    assert!(report.price_adjustment.is_some());
+    assert_eq!(
+        report.total_cost.with_scale().incl_vat.to_string(),
+        "2.4000"
+    );
+}
+
+#[test]
+fn test_ocpi_3_tariff_taxes() {
+    let mut tariff: v3::tariff::OcpiTariff = fixture!("ocpi_3/tariff.json");
+    let cdr: Cdr = fixture!("ocpi_3/cdr.json");
+
+    // When no taxes apply, the VAT of the components is dropped.
+    tariff.tax_included = v3::tariff::TaxIncluded::NotApplicable;
+    let converted = OcpiTariff::from(&tariff);
+
+    assert!(converted.elements[0].price_components[0].vat.is_none());
+
+    // A price that includes taxes needs a VAT percentage to exclude them.
+    tariff.tax_included = v3::tariff::TaxIncluded::Yes;
+    tariff.elements[1].price_components[0].vat = None;
+
+    let report = Pricer::with_v3_tariffs(&cdr, &[tariff], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(
+        report.warnings,
+        [Warning::MissingVat {
+            tariff_index: 0,
+            element_index: 1,
+            dimension: TariffDimensionType::Time,
+        }]
+    );
+}
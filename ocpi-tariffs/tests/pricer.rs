@@ -0,0 +1,380 @@
+use chrono::{Duration, NaiveDate};
+use chrono_tz::Tz;
+use ocpi_tariffs::{
+    exchange::{ExchangeRates, InvalidRate, Rate},
+    ocpi::{
+        cdr::{Cdr, OcpiCdrDimension},
+        tariff::OcpiTariff,
+    },
+    options::{FlatFee, PricerOptions, Profile, StepSizeScope, TimeStepSize},
+    pricer::Pricer,
+    types::{
+        period::{DimensionType, PeriodSplit},
+        rounding::{Rounding, RoundingScope},
+    },
+    warning::Warning,
+    Error,
+};
+use serde_json::Value;
+
+mod common;
+
+#[test]
+fn test_flat_fee_once_per_element_activation() {
+    let tariff = tariff!("flat_fee_daytime");
+    let cdr = cdr!("flat_fee_daytime", "cdr1_overnight");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .options(PricerOptions {
+            flat_fee: FlatFee::OncePerElementActivation,
+            ..PricerOptions::strict()
+        })
+        .build_report()
+        .unwrap();
+
+    let charged_periods = report
+        .periods
+        .iter()
+        .filter(|period| period.dimensions.flat.billed_volume.is_some())
+        .count();
+
+    assert_eq!(charged_periods, 2);
+    assert_eq!(
+        report.total_fixed_cost.with_scale().excl_vat.to_string(),
+        "2.0000"
+    );
+    assert_eq!(
+        report.total_cost.with_scale().excl_vat.to_string(),
+        "5.5000"
+    );
+}
+
+#[test]
+fn test_start_time_in_dst_gap() {
+    let tariff = tariff!("start_time_dst_gap");
+    let cdr = cdr!("start_time_dst_gap", "cdr1");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::Europe__Amsterdam)
+        .build_report()
+        .unwrap();
+
+    // 02:30 doesn't exist on the 26th of March in Amsterdam, the restriction becomes valid at
+    // 03:00 CEST when the clocks are moved forward.
+    let split = report
+        .periods
+        .iter()
+        .find(|period| period.split == Some(PeriodSplit::TimeOfDay))
+        .unwrap();
+
+    assert_eq!(
+        split.start_date_time.to_rfc3339(),
+        "2023-03-26T01:00:00+00:00"
+    );
+    assert_eq!(
+        report.total_fixed_cost.with_scale().excl_vat.to_string(),
+        "1.0000"
+    );
+}
+
+#[test]
+fn test_currency_mismatch() {
+    let tariff = tariff!("simple_025kwh");
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+    cdr.currency = "USD".to_string();
+
+    let result = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC).build_report();
+
+    assert!(matches!(
+        result,
+        Err(Error::CurrencyMismatch {
+            tariff_index: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_unknown_currency() {
+    let mut tariff = tariff!("simple_025kwh");
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+    cdr.currency = "EURO".to_string();
+
+    let result = Pricer::with_tariffs(&cdr, &[tariff.clone()], Tz::UTC).build_report();
+
+    assert!(matches!(
+        result,
+        Err(Error::UnknownCurrency {
+            tariff_index: None,
+            ..
+        })
+    ));
+
+    cdr.currency = "EUR".to_string();
+    tariff.currency = "EURO".to_string();
+
+    let result = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC).build_report();
+
+    assert!(matches!(
+        result,
+        Err(Error::UnknownCurrency {
+            tariff_index: Some(0),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_total_charging_time_overflow() {
+    let tariff = tariff!("step_size");
+    let mut cdr = cdr!("step_size", "cdr1");
+
+    cdr.charging_periods[1].dimensions = vec![OcpiCdrDimension::Time(Duration::MAX.into())];
+
+    let result = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC).build_report();
+
+    assert!(matches!(
+        result,
+        Err(Error::Overflow {
+            tariff_index: None,
+            period_index: 1,
+            dimension: DimensionType::Time,
+        })
+    ));
+}
+
+#[test]
+fn test_exchange_rate_conversion() {
+    let tariff = tariff!("simple_025kwh");
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+    cdr.currency = "SEK".to_string();
+
+    let mut rates = ExchangeRates::new();
+    rates.insert(
+        "EUR",
+        "SEK",
+        NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+        "11".parse().unwrap(),
+    );
+    rates.insert(
+        "EUR",
+        "SEK",
+        NaiveDate::from_ymd_opt(2022, 2, 1).unwrap(),
+        "12".parse().unwrap(),
+    );
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .exchange_rates(rates)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.exchange_rates.len(), 1);
+    assert_eq!(report.exchange_rates[0].rate.to_string(), "11");
+    assert_eq!(
+        report.total_cost.with_scale().excl_vat.to_string(),
+        "55.0000"
+    );
+}
+
+#[test]
+fn test_exchange_rate_not_positive() {
+    for rate in ["0", "0.00", "-11", "eleven"] {
+        assert_eq!(rate.parse::<Rate>(), Err(InvalidRate(rate.to_string())));
+    }
+}
+
+#[test]
+fn test_period_priced_by_tariff_id() {
+    let tariff = tariff!("simple_025kwh");
+    let mut other: Value = serde_json::to_value(&tariff).unwrap();
+    other["id"] = "17".into();
+    other["elements"][0]["price_components"][0]["price"] = serde_json::from_str("0.5").unwrap();
+    let other: OcpiTariff = serde_json::from_value(other).unwrap();
+
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+    cdr.charging_periods[0].tariff_id = Some("17".to_string());
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff, other], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_index, 1);
+    assert_eq!(report.periods[0].tariff_id, "17");
+    assert_eq!(
+        report.total_cost.with_scale().excl_vat.to_string(),
+        "10.0000"
+    );
+}
+
+#[test]
+fn test_unknown_tariff_id() {
+    let tariff = tariff!("simple_025kwh");
+    let mut other: Value = serde_json::to_value(&tariff).unwrap();
+    other["id"] = "17".into();
+    other["party_id"] = "XYZ".into();
+    let other: OcpiTariff = serde_json::from_value(other).unwrap();
+
+    // The tariff with id `17` belongs to another party than the CDR.
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+    cdr.party_id = Some("ABC".to_string());
+    cdr.charging_periods[0].tariff_id = Some("17".to_string());
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff, other], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_index, 0);
+    assert_eq!(
+        report.warnings,
+        [Warning::UnknownTariff {
+            period_index: 0,
+            tariff_id: "17".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_pricer_options() {
+    let tariff: OcpiTariff = fixture!("pricer_options/tariff.json");
+
+    let cdr: Cdr = fixture!("pricer_options/cdr.json");
+
+    let report = |options: PricerOptions| {
+        Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC)
+            .options(options)
+            .build_report()
+            .unwrap()
+    };
+
+    // The charging period is split at 15:00, and the time step size is skipped since the
+    // session ends parking.
+    let strict = report(PricerOptions::default());
+    assert_eq!(strict.profile, Profile::Strict);
+    assert_eq!(strict.periods.len(), 3);
+    assert_eq!(strict.total_energy_cost.excl_vat.to_string(), "5.3300");
+    assert_eq!(strict.billed_charging_time.to_string(), "00:45:00");
+
+    // The charging period is priced by the element that is active at 14:30.
+    let period_start = report(PricerOptions::period_start());
+    assert_eq!(period_start.profile, Profile::PeriodStart);
+    assert_eq!(period_start.periods.len(), 2);
+    assert_eq!(
+        period_start.total_energy_cost.excl_vat.to_string(),
+        "4.0000"
+    );
+
+    // Both elements bill their charging time in steps of an hour, next to the parking time.
+    let ocpi_3 = report(PricerOptions::ocpi_3());
+    assert_eq!(ocpi_3.profile, Profile::Ocpi3);
+    assert_eq!(ocpi_3.billed_charging_time.to_string(), "02:00:00");
+    assert_eq!(ocpi_3.billed_parking_time.to_string(), "00:15:00");
+
+    let options = PricerOptions {
+        time_step_size: TimeStepSize::Always,
+        ..PricerOptions::strict()
+    };
+    let custom = report(options);
+    assert_eq!(custom.profile, Profile::Custom);
+    assert_eq!(custom.options, options);
+    assert_eq!(custom.billed_charging_time.to_string(), "01:00:00");
+}
+
+#[test]
+fn test_step_size_billed_volume() {
+    let tariff = tariff!("step_size_billed_volume");
+    let cdr = cdr!("step_size_billed_volume", "cdr1");
+
+    // Rounding the billed time to 4 decimals of an hour would hide the milliseconds.
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .rounding(Rounding {
+            scope: RoundingScope::Summary,
+            ..Rounding::default()
+        })
+        .build_report()
+        .unwrap();
+
+    let dimensions = &report.periods[0].dimensions;
+
+    // The session charges 60.012 seconds. This used to be truncated to whole seconds, billing
+    // `00:01:00`, while the step size of 60 seconds rounds the milliseconds up to 2 minutes.
+    assert_eq!(dimensions.time.volume.unwrap().to_string(), "00:01:00");
+    assert_eq!(
+        dimensions.time.billed_volume.unwrap().to_string(),
+        "00:02:00"
+    );
+    assert_eq!(report.billed_charging_time.to_string(), "00:02:00");
+
+    // The 2.4 kWh is rounded up to 3 kWh. The period used to bill the 2.4 kWh that was charged,
+    // the additional volume is now part of the billed volume of the period.
+    assert_eq!(dimensions.energy.volume.unwrap().to_string(), "2.4000");
+    assert_eq!(
+        dimensions.energy.billed_volume.unwrap().to_string(),
+        "3.0000"
+    );
+    assert_eq!(report.billed_energy.to_string(), "3.0000");
+}
+
+#[test]
+fn test_step_size_scope() {
+    let tariff: OcpiTariff = fixture!("step_size_scope/tariff.json");
+
+    let cdr: Cdr = fixture!("step_size_scope/cdr.json");
+
+    let report = |step_size_scope| {
+        Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC)
+            .options(PricerOptions {
+                step_size_scope,
+                ..PricerOptions::strict()
+            })
+            .build_report()
+            .unwrap()
+    };
+
+    // The session total of 4.8 kWh is rounded up to 5 kWh in the last period.
+    let session = report(StepSizeScope::Session);
+    assert_eq!(session.billed_energy.to_string(), "5.0000");
+    assert!(session.periods[0].dimensions.energy.step_size.is_none());
+
+    let adjustment = session.periods[1].dimensions.energy.step_size.unwrap();
+    assert_eq!(adjustment.volume.to_string(), "0.2000");
+    assert_eq!(adjustment.step_size, 1000);
+    assert_eq!(adjustment.scope, StepSizeScope::Session);
+    assert_eq!(adjustment.tariff_element_index, 1);
+    assert_eq!(
+        session.periods[1]
+            .dimensions
+            .energy
+            .billed_volume
+            .unwrap()
+            .to_string(),
+        "2.6000"
+    );
+
+    // Both elements round up the 2.4 kWh that they priced to 3 kWh.
+    let element = report(StepSizeScope::Element);
+    assert_eq!(element.billed_energy.to_string(), "6.0000");
+
+    for (period, element_index) in element.periods.iter().zip([0, 1]) {
+        let adjustment = period.dimensions.energy.step_size.unwrap();
+        assert_eq!(adjustment.volume.to_string(), "0.6000");
+        assert_eq!(adjustment.tariff_element_index, element_index);
+    }
+
+    // Only the element that was active last rounds up its volume.
+    let last_element = report(StepSizeScope::LastElement);
+    assert_eq!(last_element.billed_energy.to_string(), "5.4000");
+    assert!(last_element.periods[0]
+        .dimensions
+        .energy
+        .step_size
+        .is_none());
+    assert_eq!(
+        last_element.periods[1]
+            .dimensions
+            .energy
+            .step_size
+            .unwrap()
+            .volume
+            .to_string(),
+        "0.6000"
+    );
+}
@@ -0,0 +1,249 @@
+use chrono_tz::Tz;
+use ocpi_tariffs::{
+    ocpi::{cdr::Cdr, tariff::OcpiTariff},
+    pricer::Pricer,
+    types::rounding::{Rounding, RoundingMode, RoundingScope, VatRounding},
+};
+use serde_json::Value;
+
+mod common;
+
+#[test]
+fn test_round_to_minor_units() {
+    let mut tariff = tariff!("simple_025kwh");
+    tariff.currency = "JPY".to_string();
+
+    let cdr: Cdr = fixture!("round_to_minor_units/cdr.json");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    // The yen has no minor unit, 5.375 is rounded to 5.
+    assert_eq!(report.currency.code(), "JPY");
+    assert_eq!(report.currency.format(report.total_cost.excl_vat), "5");
+    assert_eq!(report.currency.format(report.total_cost.incl_vat), "6");
+    assert_eq!(format!("{:.2}", report.total_cost.incl_vat), "6.00");
+}
+
+#[test]
+fn test_tax_summary() {
+    let tariff: OcpiTariff = fixture!("tax_summary/tariff.json");
+
+    let cdr: Cdr = fixture!("tax_summary/cdr.json");
+
+    let tax_summary = |rounding: Rounding| -> Vec<_> {
+        let report = Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC)
+            .rounding(rounding)
+            .build_report()
+            .unwrap();
+
+        report
+            .tax_summary
+            .iter()
+            .map(|rate| {
+                (
+                    rate.vat.map(|vat| vat.to_string()),
+                    rate.taxable_amount.to_string(),
+                    rate.tax_amount.to_string(),
+                    rate.gross_amount.to_string(),
+                )
+            })
+            .collect()
+    };
+
+    let summary = tax_summary(Rounding {
+        money_decimals: Some(4),
+        ..Rounding::default()
+    });
+
+    // The VAT over the energy is rounded once, at the level of the summary.
+    assert_eq!(
+        summary,
+        [
+            (
+                Some("9".into()),
+                "1.0000".into(),
+                "0.0900".into(),
+                "1.0900".into()
+            ),
+            (
+                Some("21".into()),
+                "2.4690".into(),
+                "0.5185".into(),
+                "2.9875".into()
+            ),
+            (None, "0.3330".into(), "0.0000".into(), "0.3330".into()),
+        ]
+    );
+
+    // By default the summary is rounded to the cents of the euro.
+    assert_eq!(
+        tax_summary(Rounding::default()),
+        [
+            (
+                Some("9".into()),
+                "1.0000".into(),
+                "0.0900".into(),
+                "1.0900".into()
+            ),
+            (
+                Some("21".into()),
+                "2.4700".into(),
+                "0.5200".into(),
+                "2.9900".into()
+            ),
+            (None, "0.3300".into(), "0.0000".into(), "0.3300".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_tax_summary_remainder() {
+    let mut tariff: Value = fixture!("tax_summary/tariff.json");
+    let components = &mut tariff["elements"][0]["price_components"];
+    components[0]["price"] = serde_json::from_str("0.015").unwrap();
+    components[1]["price"] = serde_json::from_str("0").unwrap();
+    components[2]["price"] = serde_json::from_str("0.006").unwrap();
+    let tariff: OcpiTariff = serde_json::from_value(tariff).unwrap();
+
+    let cdr: Cdr = fixture!("tax_summary/cdr.json");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .rounding(Rounding {
+            scope: RoundingScope::Summary,
+            ..Rounding::default()
+        })
+        .build_report()
+        .unwrap();
+
+    let summary: Vec<_> = report
+        .tax_summary
+        .iter()
+        .map(|rate| {
+            (
+                rate.taxable_amount.to_string(),
+                rate.gross_amount.to_string(),
+            )
+        })
+        .collect();
+
+    // Separately the rates round up to 0.03, the cent that the total of 0.021 lacks is taken
+    // from the largest rate.
+    assert_eq!(
+        summary,
+        [
+            ("0.0100".into(), "0.0100".into()),
+            ("0.0000".into(), "0.0000".into()),
+            ("0.0100".into(), "0.0100".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_tax_summary_min_price() {
+    let tariff: OcpiTariff = fixture!("tax_summary_min_price/tariff.json");
+    let cdr: Cdr = fixture!("tax_summary_min_price/cdr.json");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    // Nothing is charged, the `min_price` is taxed at the VAT of the price components.
+    assert_eq!(report.tax_summary.len(), 1);
+
+    let rate = &report.tax_summary[0];
+    assert_eq!(rate.vat.map(|vat| vat.to_string()), Some("21".into()));
+    assert_eq!(rate.taxable_amount.to_string(), "2.0000");
+    assert_eq!(rate.tax_amount.to_string(), "0.4200");
+    assert_eq!(rate.gross_amount, report.total_cost.incl_vat);
+}
+
+#[test]
+fn test_tax_summary_min_price_vat() {
+    let mut tariff: OcpiTariff = fixture!("tax_summary_min_price/tariff.json");
+    let cdr: Cdr = fixture!("tax_summary_min_price/cdr.json");
+
+    // The amounts of the `min_price` don't correspond with a VAT of exactly 21%.
+    tariff.min_price = Some(
+        serde_json::from_value(serde_json::json!({ "excl_vat": 2.00, "incl_vat": 2.43 })).unwrap(),
+    );
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.tax_summary.len(), 1);
+
+    let rate = &report.tax_summary[0];
+    assert_eq!(rate.vat.map(|vat| vat.to_string()), Some("21".into()));
+    assert_eq!(rate.taxable_amount.to_string(), "2.0000");
+    assert_eq!(rate.tax_amount.to_string(), "0.4300");
+    assert_eq!(rate.gross_amount.to_string(), "2.4300");
+    assert_eq!(rate.gross_amount, report.total_cost.incl_vat);
+}
+
+#[test]
+fn test_rounding() {
+    let tariff: OcpiTariff = fixture!("rounding/tariff.json");
+
+    let cdr: Cdr = fixture!("rounding/cdr.json");
+
+    let total_cost = |rounding: Rounding| {
+        let report = Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC)
+            .rounding(rounding)
+            .build_report()
+            .unwrap();
+
+        assert_eq!(report.rounding, rounding);
+
+        (
+            report.total_cost.excl_vat.to_string(),
+            report.total_cost.incl_vat.to_string(),
+        )
+    };
+
+    let cents = Rounding {
+        money_decimals: Some(2),
+        ..Rounding::default()
+    };
+
+    // By default the totals are rounded to the cents of the euro.
+    assert_eq!(
+        total_cost(Rounding::default()),
+        ("0.2500".into(), "0.3000".into())
+    );
+
+    assert_eq!(
+        total_cost(Rounding {
+            scope: RoundingScope::Summary,
+            ..cents
+        }),
+        ("0.2500".into(), "0.3025".into())
+    );
+
+    // Each period costs 0.125 excluding VAT.
+    assert_eq!(
+        total_cost(Rounding {
+            scope: RoundingScope::Period,
+            ..cents
+        }),
+        ("0.2600".into(), "0.3200".into())
+    );
+    assert_eq!(
+        total_cost(Rounding {
+            scope: RoundingScope::Period,
+            mode: RoundingMode::HalfEven,
+            ..cents
+        }),
+        ("0.2400".into(), "0.3000".into())
+    );
+    assert_eq!(
+        total_cost(Rounding {
+            scope: RoundingScope::Period,
+            vat: VatRounding::AfterVat,
+            ..cents
+        }),
+        ("0.2600".into(), "0.3000".into())
+    );
+}
@@ -0,0 +1,189 @@
+use chrono_tz::Tz;
+use ocpi_tariffs::{
+    ocpi::tariff::{OcpiTariff, ProfileType},
+    pricer::Pricer,
+    selector::{
+        candidates, LatestUpdateSelector, OverrideSelector, SelectionContext, TariffCandidate,
+        TariffSelection, TariffSelector, TokenSelector,
+    },
+};
+
+mod common;
+
+#[test]
+fn test_tariff_selection_by_type() {
+    let tariff = |id: &str, tariff_type: &str| -> OcpiTariff {
+        let mut tariff = serde_json::to_value(tariff!("simple_025kwh")).unwrap();
+        tariff["id"] = id.into();
+        tariff["type"] = tariff_type.into();
+        serde_json::from_value(tariff).unwrap()
+    };
+
+    let tariffs = [
+        tariff("cheap", "PROFILE_CHEAP"),
+        tariff("regular", "REGULAR"),
+        tariff("ad-hoc", "AD_HOC_PAYMENT"),
+    ];
+
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "regular");
+    let selections: Vec<_> = report
+        .tariff_selection
+        .iter()
+        .map(|candidate| candidate.selection.clone())
+        .collect();
+    assert_eq!(
+        selections,
+        [
+            TariffSelection::TypeMismatch,
+            TariffSelection::Regular,
+            TariffSelection::TypeMismatch
+        ]
+    );
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .charging_profile(ProfileType::Cheap)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "cheap");
+    assert_eq!(
+        report.tariff_selection[1].selection,
+        TariffSelection::Superseded
+    );
+
+    cdr.cdr_token = fixture!("tariff_selection/token_ad_hoc.json");
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .charging_profile(ProfileType::Cheap)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "ad-hoc");
+    assert_eq!(
+        report.tariff_selection[2].selection,
+        TariffSelection::Preferred
+    );
+}
+
+#[test]
+fn test_tariff_selector() {
+    let tariff = |id: &str, last_updated: &str, end_date_time: &str| -> OcpiTariff {
+        let mut tariff = serde_json::to_value(tariff!("simple_025kwh")).unwrap();
+        tariff["id"] = id.into();
+        tariff["last_updated"] = last_updated.into();
+        tariff["end_date_time"] = end_date_time.into();
+        serde_json::from_value(tariff).unwrap()
+    };
+
+    let tariffs = [
+        tariff("old", "2021-01-01T00:00:00Z", "2030-01-01T00:00:00Z"),
+        tariff("new", "2021-06-01T00:00:00Z", "2030-01-01T00:00:00Z"),
+        tariff("expired", "2021-09-01T00:00:00Z", "2022-01-01T00:00:00Z"),
+    ];
+
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "old");
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .tariff_selector(LatestUpdateSelector)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "new");
+    let selections: Vec<_> = report
+        .tariff_selection
+        .iter()
+        .map(|candidate| candidate.selection.clone())
+        .collect();
+    assert_eq!(
+        selections,
+        [
+            TariffSelection::Superseded,
+            TariffSelection::LatestUpdate,
+            TariffSelection::OutsideValidity
+        ]
+    );
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .tariff_selector(OverrideSelector::new("expired"))
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "expired");
+    assert_eq!(
+        report.tariff_selection[2].selection,
+        TariffSelection::Override
+    );
+
+    let selector = || TokenSelector::new(LatestUpdateSelector).assign("012345678", "old");
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .tariff_selector(selector())
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "new");
+
+    cdr.cdr_token = fixture!("tariff_selection/token_rfid.json");
+
+    let report = Pricer::with_tariffs(&cdr, &tariffs, Tz::UTC)
+        .tariff_selector(selector())
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "old");
+    assert_eq!(report.tariff_selection[0].selection, TariffSelection::Token);
+}
+
+/// Selects the last tariff that is provided.
+struct LastSelector;
+
+impl TariffSelector for LastSelector {
+    fn select(
+        &self,
+        tariffs: &[OcpiTariff],
+        context: &SelectionContext<'_>,
+    ) -> Vec<TariffCandidate> {
+        let selected = tariffs
+            .len()
+            .checked_sub(1)
+            .map(|index| (index, TariffSelection::Custom("last".to_string())));
+
+        candidates(tariffs, context, selected)
+    }
+}
+
+#[test]
+fn test_custom_tariff_selector() {
+    let mut other = serde_json::to_value(tariff!("simple_025kwh")).unwrap();
+    other["id"] = "other".into();
+    let other: OcpiTariff = serde_json::from_value(other).unwrap();
+
+    let cdr = cdr!("simple_025kwh", "cdr1");
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff!("simple_025kwh"), other], Tz::UTC)
+        .tariff_selector(LastSelector)
+        .build_report()
+        .unwrap();
+
+    assert_eq!(report.periods[0].tariff_id, "other");
+    assert_eq!(
+        report.tariff_selection[0].selection,
+        TariffSelection::Superseded
+    );
+    assert_eq!(
+        report.tariff_selection[1].selection,
+        TariffSelection::Custom("last".to_string())
+    );
+}
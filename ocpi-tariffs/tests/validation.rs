@@ -0,0 +1,184 @@
+use chrono::Duration;
+use chrono_tz::Tz;
+use ocpi_tariffs::{
+    ocpi::{
+        cdr::{Cdr, CdrDimensionType},
+        tariff::{OcpiTariff, TariffDimensionType},
+    },
+    pricer::{PriceLimit, Pricer},
+    types::period::DimensionType,
+    validation::{validate_cdr, CdrIssue},
+    warning::Warning,
+    Error,
+};
+
+mod common;
+
+#[test]
+fn test_period_after_stop_is_inconsistent() {
+    let tariff = tariff!("simple_025kwh");
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+
+    let last_period = cdr.charging_periods.len() - 1;
+    cdr.charging_periods[last_period].start_date_time = cdr.stop_date_time + Duration::hours(1);
+
+    let result = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC).build_report();
+
+    assert!(matches!(
+        result,
+        Err(Error::InconsistentCdr { period_index }) if period_index == last_period
+    ));
+}
+
+#[test]
+fn test_validate_cdr_duplicate_dimension() {
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+
+    let dimension = cdr.charging_periods[0].dimensions[0].clone();
+    cdr.charging_periods[0].dimensions.push(dimension);
+
+    let issues = validate_cdr(&cdr);
+
+    assert_eq!(issues.len(), 2);
+    assert_eq!(
+        issues[0],
+        CdrIssue::DuplicateDimension {
+            period_index: 0,
+            dimension: CdrDimensionType::Energy
+        }
+    );
+    assert!(matches!(issues[1], CdrIssue::TotalEnergyMismatch { .. }));
+}
+
+#[test]
+fn test_pricer_rejects_invalid_cdr() {
+    let tariff = tariff!("step_size");
+    let cdr = cdr!("step_size", "cdr1");
+
+    let result = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .validate_cdr(true)
+        .build_report();
+
+    let Err(Error::InvalidCdr(issues)) = result else {
+        panic!("expected the CDR to be invalid");
+    };
+
+    assert!(matches!(
+        issues.as_slice(),
+        [CdrIssue::DurationExceedsPeriod {
+            period_index: 1,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_price_limit_decimals() {
+    let mut tariff = tariff!("simple_025kwh");
+    tariff.currency = "JPY".to_string();
+    tariff.min_price = Some(
+        serde_json::from_value(serde_json::json!({ "excl_vat": 1.50, "incl_vat": 1.80 })).unwrap(),
+    );
+    tariff.max_price = Some(
+        serde_json::from_value(serde_json::json!({ "excl_vat": 100.0, "incl_vat": 121 })).unwrap(),
+    );
+
+    let mut cdr = cdr!("simple_025kwh", "cdr1");
+    cdr.currency = "JPY".to_string();
+
+    let report = Pricer::with_tariffs(&cdr, &[tariff], Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    // The yen has no minor unit, the unit price of 0.25 is allowed but the `min_price` isn't.
+    assert_eq!(
+        report.warnings,
+        [Warning::PriceLimitDecimals {
+            tariff_index: 0,
+            limit: PriceLimit::MinPrice,
+            minor_units: 0,
+        }]
+    );
+}
+
+#[test]
+fn test_warnings() {
+    let tariff: OcpiTariff = fixture!("warnings/tariff.json");
+
+    let cdr: Cdr = fixture!("warnings/cdr.json");
+
+    let report = Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    // The energy after 15:00 isn't priced, since only the first element has an energy component.
+    assert_eq!(
+        report.warnings,
+        [
+            Warning::UnusedVolume {
+                period_index: 0,
+                dimension: CdrDimensionType::EnergyExport,
+            },
+            Warning::DuplicateComponent {
+                tariff_index: 0,
+                element_index: 0,
+                dimension: TariffDimensionType::Energy,
+            },
+            Warning::UnpricedVolume {
+                tariff_index: 0,
+                period_index: 1,
+                dimension: DimensionType::Energy,
+            },
+        ]
+    );
+
+    // Only the first of the duplicate components is used.
+    assert_eq!(report.total_energy_cost.excl_vat.to_string(), "2.0000");
+
+    // The dimensions are named as in OCPI.
+    assert_eq!(
+        report.warnings[0].to_string(),
+        "Charging period 0 has a `ENERGY_EXPORT` volume that isn't priced"
+    );
+    assert_eq!(
+        report.warnings[1].to_string(),
+        "Element 0 of tariff 0 contains more than one `ENERGY` price component, only the first is used"
+    );
+    assert_eq!(
+        report.warnings[2].to_string(),
+        "Period 1 has a `ENERGY` volume, but no active element of tariff 0 prices it"
+    );
+}
+
+#[test]
+fn test_untariffed_volume_warning() {
+    let tariff: OcpiTariff = fixture!("interpret/tariff.json");
+
+    let cdr: Cdr = fixture!("warnings/cdr.json");
+
+    let report = Pricer::with_tariffs(&cdr, std::slice::from_ref(&tariff), Tz::UTC)
+        .build_report()
+        .unwrap();
+
+    // The tariff only prices energy, the time of both periods is reported once.
+    assert_eq!(report.periods.len(), 2);
+    assert_eq!(
+        report.warnings,
+        [
+            Warning::UnusedVolume {
+                period_index: 0,
+                dimension: CdrDimensionType::EnergyExport,
+            },
+            Warning::UntariffedVolume {
+                tariff_index: 0,
+                period_index: 0,
+                dimension: DimensionType::Time,
+            },
+        ]
+    );
+
+    assert_eq!(
+        report.warnings[1].to_string(),
+        "Period 0 has a `TIME` volume, but tariff 0 has no price component for it"
+    );
+}